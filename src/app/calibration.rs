@@ -0,0 +1,89 @@
+use crate::app::states::settings::Interpolation;
+use egui::{Context, Id};
+use serde::{Deserialize, Serialize};
+
+const ID: &str = "Calibration";
+
+/// Retention index calibration against an alkane or FAME standard
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub(crate) struct Calibration {
+    /// Standard run name
+    pub(crate) name: String,
+    /// Hold-up (dead) time, minutes
+    pub(crate) dead_time: f64,
+    /// Retention times (minutes) and carbon numbers of the standard peaks,
+    /// increasing in both
+    pub(crate) points: Vec<(f64, u32)>,
+}
+
+impl Calibration {
+    /// Builds a calibration, keeping the points increasing in both
+    /// retention time and carbon number
+    pub(crate) fn new(name: String, dead_time: f64, mut points: Vec<(f64, u32)>) -> Self {
+        points.sort_by(|left, right| left.0.total_cmp(&right.0));
+        let mut monotonic = Vec::<(f64, u32)>::with_capacity(points.len());
+        for point in points {
+            if monotonic
+                .last()
+                .is_none_or(|last| last.0 < point.0 && last.1 < point.1)
+            {
+                monotonic.push(point);
+            }
+        }
+        Self {
+            name,
+            dead_time,
+            points: monotonic,
+        }
+    }
+
+    /// Calibration stored in the context
+    pub(crate) fn get(ctx: &Context) -> Option<Self> {
+        ctx.data_mut(|data| data.get_persisted(Id::new(ID)))
+    }
+
+    pub(crate) fn set(ctx: &Context, calibration: Self) {
+        ctx.data_mut(|data| data.insert_persisted(Id::new(ID), calibration));
+    }
+
+    pub(crate) fn clear(ctx: &Context) {
+        ctx.data_mut(|data| data.remove::<Self>(Id::new(ID)));
+    }
+
+    /// Equivalent chain length, the carbon number interpolated between the
+    /// bracketing standards (extrapolated from the outer pairs)
+    pub(crate) fn equivalent_chain_length(
+        &self,
+        retention_time: f64,
+        interpolation: Interpolation,
+    ) -> Option<f64> {
+        let last = self.points.len().checked_sub(2)?;
+        let index = self
+            .points
+            .partition_point(|&(standard, _)| standard <= retention_time)
+            .saturating_sub(1)
+            .min(last);
+        let (t0, n0) = self.points[index];
+        let (t1, n1) = self.points[index + 1];
+        let scale = |retention_time: f64| match interpolation {
+            // van den Dool and Kratz, temperature programmed
+            Interpolation::Linear => Some(retention_time),
+            // Kovats, isothermal, adjusted retention times
+            Interpolation::Logarithmic => {
+                let adjusted = retention_time - self.dead_time;
+                (adjusted > 0.0).then(|| adjusted.ln())
+            }
+        };
+        let (x, x0, x1) = (scale(retention_time)?, scale(t0)?, scale(t1)?);
+        Some(n0 as f64 + (n1 - n0) as f64 * (x - x0) / (x1 - x0))
+    }
+
+    /// Retention index, 100 × equivalent chain length
+    pub(crate) fn retention_index(
+        &self,
+        retention_time: f64,
+        interpolation: Interpolation,
+    ) -> Option<f64> {
+        Some(100.0 * self.equivalent_chain_length(retention_time, interpolation)?)
+    }
+}
@@ -0,0 +1,469 @@
+//! Headless command line interface
+//!
+//! Runs the computers of the GUI on files without a window:
+//!
+//! ```text
+//! ms_viewer <COMMAND> [OPTIONS] <INPUT>...
+//! ```
+//!
+//! Inputs are run files or folders. The run files of a folder are processed,
+//! unrecognised files, libraries and outputs (`{STEM}.{COMMAND}.{EXT}`) are
+//! skipped. A failed file is reported and the rest of the batch goes on, the
+//! exit code is 1 if any file failed.
+
+use crate::{
+    app::{
+        computers::{
+            extraction::{Computer as ExtractionComputer, Key as ExtractionKey},
+            peaks::{Computer as PeaksComputer, Key as PeaksKey, Peak},
+            search::{Computer as SearchComputer, Key as SearchKey},
+            table::{Computer as TableComputer, Key as TableKey},
+        },
+        data::{Data, Format},
+        library::Library,
+        readers::{self, Kind, stem},
+        states::settings::{Ion, Settings, Sort},
+    },
+    r#const::*,
+    utils::hash::{HashedDataFrame, HashedMetaDataFrame},
+};
+use anyhow::{Context as _, Result, bail, ensure};
+use const_format::formatcp;
+use egui::Color32;
+use metadata::{NAME, polars::MetaDataFrame};
+use polars::prelude::*;
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+const COMMAND: &str = "Command";
+const COMMANDS: [Command; 5] = [
+    Command::Convert,
+    Command::Tic,
+    Command::Peaks,
+    Command::Eic,
+    Command::Search,
+];
+/// Number of leading bytes inspected to recognise a run
+const SNIFF: usize = 1024;
+
+const HELP: &str = "\
+Usage: ms_viewer <COMMAND> [OPTIONS] <INPUT>...
+       ms_viewer [--table | --plot] [FILE]...
+
+Without a command the window opens with the files as table (or plot) panes.
+
+Commands:
+  convert  Raw points (retention time, mass to charge, signal)
+  tic      Total ion chromatogram
+  peaks    Integrated peaks of the total ion chromatogram
+  eic      Extracted ion chromatograms
+  search   Library search of the peak apex spectra
+  help     Print this help
+
+Inputs are run files or folders, every file of a folder is processed.
+
+Options:
+  -o, --output <DIR>       Output folder [default: .]
+  -f, --format <FORMAT>    csv, parquet or ron [default: csv]
+  -s, --settings <FILE>    Settings RON, as persisted by the GUI
+      --mz <MASS_TO_CHARGE>  Extracted ion (eic), repeatable, the settings ions by default
+      --tolerance <DA>     Extracted ion tolerance [default: 0.5]
+  -l, --library <FILE>     MSP library (search)";
+
+/// Whether the arguments start with a command
+pub fn is_command(args: &[String]) -> bool {
+    args.first().is_some_and(|command| {
+        matches!(
+            command.as_str(),
+            "convert" | "tic" | "peaks" | "eic" | "search" | "help" | "--help" | "-h"
+        )
+    })
+}
+
+/// Runs the command of the arguments
+pub fn run(args: &[String]) -> Result<()> {
+    let Some((command, args)) = args.split_first() else {
+        bail!("{HELP}");
+    };
+    let command = match command.as_str() {
+        "convert" => Command::Convert,
+        "tic" => Command::Tic,
+        "peaks" => Command::Peaks,
+        "eic" => Command::Eic,
+        "search" => Command::Search,
+        "help" | "--help" | "-h" => {
+            println!("{HELP}");
+            return Ok(());
+        }
+        command => bail!("unknown command {command}\n\n{HELP}"),
+    };
+    let options = Options::parse(args)?;
+    let library = match (&options.library, command) {
+        (Some(path), _) => Some(Library::new(readers::msp::read(&fs::read(path)?)?)),
+        (None, Command::Search) => bail!("search requires a library (--library)"),
+        (None, _) => None,
+    };
+    fs::create_dir_all(&options.output)?;
+    // A failed file is reported and the batch goes on
+    let inputs = inputs(&options.inputs)?;
+    let mut failed = 0;
+    for path in &inputs {
+        let name = path.display().to_string();
+        match process(command, path, &options, library.as_ref()) {
+            Ok(output) => eprintln!("{name} -> {}", output.display()),
+            Err(error) => {
+                eprintln!("{name}: {error:#}");
+                failed += 1;
+            }
+        }
+    }
+    ensure!(failed == 0, "{failed} of {} files failed", inputs.len());
+    Ok(())
+}
+
+/// Runs the command on a file, returns the output path
+fn process(
+    command: Command,
+    path: &Path,
+    options: &Options,
+    library: Option<&Library>,
+) -> Result<PathBuf> {
+    let name = path.display().to_string();
+    let frame = readers::read(&name, &fs::read(path)?)?;
+    let mut output = match command {
+        Command::Convert => frame.clone(),
+        Command::Tic => tic(&frame, &options.settings)?,
+        Command::Peaks => peaks(&frame, &options.settings)?,
+        Command::Eic => eic(&frame, options)?,
+        Command::Search => search(&frame, library.context("library")?, &options.settings)?,
+    };
+    output
+        .meta
+        .insert(COMMAND.to_owned(), command.text().to_owned());
+    let path = options.output.join(format!(
+        "{}.{}.{}",
+        stem(&name),
+        command.text(),
+        options.format.extension(),
+    ));
+    options
+        .format
+        .write(&path, output)
+        .with_context(|| path.display().to_string())?;
+    Ok(path)
+}
+
+/// Total ion chromatogram: retention time (minutes), signal and baseline
+fn tic(frame: &HashedMetaDataFrame, settings: &Settings) -> Result<HashedMetaDataFrame> {
+    let data_frame = retention_time(frame, settings)?;
+    let mut columns = vec![
+        col(RETENTION_TIME),
+        col(formatcp!("_{SIGNAL}.{SUM}")).alias(SIGNAL),
+    ];
+    if data_frame.schema().contains(formatcp!("_{BASELINE}")) {
+        columns.push(col(formatcp!("_{BASELINE}")).alias(BASELINE));
+    }
+    let data_frame = data_frame
+        .data_frame
+        .clone()
+        .lazy()
+        .select(columns)
+        .collect()?;
+    output(frame, data_frame)
+}
+
+/// Integrated peaks of the total ion chromatogram
+fn peaks(frame: &HashedMetaDataFrame, settings: &Settings) -> Result<HashedMetaDataFrame> {
+    let data_frame = retention_time(frame, settings)?;
+    let value = PeaksComputer.try_compute(PeaksKey::new(&data_frame, settings))?;
+    let column = |name: &str, f: fn(&Peak) -> f64| {
+        Column::new(name.into(), value.peaks.iter().map(f).collect::<Vec<_>>())
+    };
+    let data_frame = DataFrame::new(vec![
+        column("Apex", |peak| peak.apex[0]),
+        column("Start", |peak| peak.start[0]),
+        column("End", |peak| peak.end[0]),
+        column("Height", |peak| peak.height),
+        column("Area", |peak| peak.area),
+        column("Percent", |peak| peak.percent),
+        Column::new(
+            "Skimmed".into(),
+            value
+                .peaks
+                .iter()
+                .map(|peak| peak.skimmed)
+                .collect::<Vec<_>>(),
+        ),
+    ])?;
+    output(frame, data_frame)
+}
+
+/// Extracted ion chromatograms: ion, retention time (minutes) and signal
+fn eic(frame: &HashedMetaDataFrame, options: &Options) -> Result<HashedMetaDataFrame> {
+    let mut settings = Settings {
+        explode: false,
+        sort: Sort::MassToCharge,
+        ..options.settings.clone()
+    };
+    settings.extraction.sum = false;
+    if !options.ions.is_empty() {
+        settings.extraction.ions = options
+            .ions
+            .iter()
+            .map(|&mass_to_charge| Ion {
+                mass_to_charge,
+                tolerance: options.tolerance,
+                label: String::new(),
+                color: Color32::default(),
+            })
+            .collect();
+    }
+    let data_frame = TableComputer.try_compute(TableKey::new(&frame.data, &settings))?;
+    let value = ExtractionComputer.try_compute(ExtractionKey::new(&data_frame, &settings))?;
+    let (mut ions, mut retention_times, mut signals) = (Vec::new(), Vec::new(), Vec::new());
+    for trace in value.traces {
+        for [retention_time, signal] in trace.points {
+            ions.push(trace.name.clone());
+            retention_times.push(retention_time);
+            signals.push(signal);
+        }
+    }
+    let data_frame = DataFrame::new(vec![
+        Column::new("Ion".into(), ions),
+        Column::new(RETENTION_TIME.into(), retention_times),
+        Column::new(SIGNAL.into(), signals),
+    ])?;
+    output(frame, data_frame)
+}
+
+/// Library hits of the peak apex spectra
+fn search(
+    frame: &HashedMetaDataFrame,
+    library: &Library,
+    settings: &Settings,
+) -> Result<HashedMetaDataFrame> {
+    let data_frame = retention_time(frame, settings)?;
+    let peaks = PeaksComputer.try_compute(PeaksKey::new(&data_frame, settings))?;
+    let minutes = data_frame[RETENTION_TIME].f64()?;
+    let (mut apexes, mut ranks, mut names) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut formulas, mut cas, mut forwards, mut reverses) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for peak in &peaks.peaks {
+        // Row of the apex scan
+        let Some(index) = minutes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, minutes)| Some((index, minutes?)))
+            .min_by(|left, right| {
+                (left.1 - peak.apex[0])
+                    .abs()
+                    .total_cmp(&(right.1 - peak.apex[0]).abs())
+            })
+            .map(|(index, _)| index)
+        else {
+            continue;
+        };
+        let value =
+            SearchComputer.try_compute(SearchKey::new(&data_frame, index, library, settings))?;
+        for (rank, hit) in value.hits.iter().enumerate() {
+            let entry = &library.entries[hit.index];
+            apexes.push(peak.apex[0]);
+            ranks.push(rank as u32 + 1);
+            names.push(entry.name.clone());
+            formulas.push(entry.formula.clone());
+            cas.push(entry.cas.clone());
+            forwards.push(hit.forward);
+            reverses.push(hit.reverse);
+        }
+    }
+    let data_frame = DataFrame::new(vec![
+        Column::new("Apex".into(), apexes),
+        Column::new("Rank".into(), ranks),
+        Column::new("Name".into(), names),
+        Column::new("Formula".into(), formulas),
+        Column::new("CAS".into(), cas),
+        Column::new("Forward".into(), forwards),
+        Column::new("Reverse".into(), reverses),
+    ])?;
+    output(frame, data_frame)
+}
+
+/// Frame grouped by retention time, as shown by the table pane
+fn retention_time(frame: &HashedMetaDataFrame, settings: &Settings) -> Result<HashedDataFrame> {
+    let settings = Settings {
+        explode: false,
+        sort: Sort::RetentionTime,
+        ..settings.clone()
+    };
+    Ok(TableComputer.try_compute(TableKey::new(&frame.data, &settings))?)
+}
+
+/// Output frame with the metadata of the input
+fn output(frame: &HashedMetaDataFrame, data_frame: DataFrame) -> Result<HashedMetaDataFrame> {
+    let mut meta = frame.meta.clone();
+    meta.entry(NAME.to_owned()).or_default();
+    Ok(MetaDataFrame::new(meta, HashedDataFrame::new(data_frame)?))
+}
+
+/// Files of the inputs, folders are expanded to their run files
+fn inputs(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            let mut files = fs::read_dir(input)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<_>>>()?;
+            files.retain(|path| path.is_file() && is_run(path));
+            files.sort();
+            paths.extend(files);
+        } else {
+            paths.push(input.clone());
+        }
+    }
+    if paths.is_empty() {
+        bail!("no input files\n\n{HELP}");
+    }
+    Ok(paths)
+}
+
+/// Whether a folder file is a run: a recognised kind, not a library and not
+/// an output of a command
+fn is_run(path: &Path) -> bool {
+    let name = path.display().to_string();
+    if COMMANDS
+        .iter()
+        .any(|command| name.contains(&format!(".{}.", command.text())))
+    {
+        return false;
+    }
+    let mut head = Vec::with_capacity(SNIFF);
+    if File::open(path)
+        .and_then(|file| file.take(SNIFF as _).read_to_end(&mut head))
+        .is_err()
+    {
+        return false;
+    }
+    match Kind::new(&name, &head) {
+        Kind::Msp => false,
+        // Unknown files fall back to RON
+        Kind::Ron => name.to_lowercase().ends_with(".ron"),
+        _ => true,
+    }
+}
+
+/// Command
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Command {
+    Convert,
+    Tic,
+    Peaks,
+    Eic,
+    Search,
+}
+
+impl Command {
+    fn text(&self) -> &'static str {
+        match self {
+            Self::Convert => "convert",
+            Self::Tic => "tic",
+            Self::Peaks => "peaks",
+            Self::Eic => "eic",
+            Self::Search => "search",
+        }
+    }
+}
+
+/// Options
+#[derive(Debug)]
+struct Options {
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    format: Output,
+    settings: Settings,
+    ions: Vec<f64>,
+    tolerance: f64,
+    library: Option<PathBuf>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut options = Self {
+            inputs: Vec::new(),
+            output: PathBuf::from("."),
+            format: Output::Csv,
+            settings: Settings::default(),
+            ions: Vec::new(),
+            tolerance: 0.5,
+            library: None,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("{arg} requires a value"))
+            };
+            match arg.as_str() {
+                "-o" | "--output" => options.output = value()?.into(),
+                "-f" | "--format" => options.format = value()?.parse()?,
+                "-s" | "--settings" => {
+                    let path = value()?;
+                    options.settings =
+                        ron::de::from_bytes(&fs::read(path)?).with_context(|| path.clone())?;
+                }
+                "--mz" => options.ions.push(value()?.parse()?),
+                "--tolerance" => options.tolerance = value()?.parse()?,
+                "-l" | "--library" => options.library = Some(value()?.into()),
+                option if option.starts_with('-') => bail!("unknown option {option}\n\n{HELP}"),
+                input => options.inputs.push(input.into()),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Output format
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Output {
+    Csv,
+    Parquet,
+    Ron,
+}
+
+impl Output {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => Format::Parquet.extension(),
+            Self::Ron => Format::MetaRon.extension(),
+        }
+    }
+
+    /// Writes the frame, CSV keeps the data only
+    fn write(&self, path: &Path, frame: HashedMetaDataFrame) -> Result<()> {
+        match self {
+            Self::Csv => {
+                let mut data_frame = frame.data.data_frame;
+                CsvWriter::new(File::create(path)?).finish(&mut data_frame)?;
+            }
+            Self::Parquet => Data { frame }.save(path, Format::Parquet)?,
+            Self::Ron => Data { frame }.save(path, Format::MetaRon)?,
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Output {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            "ron" => Ok(Self::Ron),
+            _ => bail!("unknown format {text}, expected csv, parquet or ron"),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::r#const::*;
+use polars::prelude::*;
+use std::iter::zip;
+
+/// Milliseconds in a minute
+const MINUTES: f64 = 60_000.0;
+
+/// Total ion chromatogram of a long frame: retention time (minutes) and summed
+/// signal
+pub(crate) fn chromatogram(data_frame: &DataFrame) -> PolarsResult<(Vec<f64>, Vec<f64>)> {
+    let data_frame = data_frame
+        .clone()
+        .lazy()
+        .group_by([col(RETENTION_TIME)])
+        .agg([col(SIGNAL).cast(DataType::Float64).sum()])
+        .sort([RETENTION_TIME], Default::default())
+        .select([
+            col(RETENTION_TIME).cast(DataType::Float64) / lit(MINUTES),
+            col(SIGNAL),
+        ])
+        .collect()?;
+    Ok(
+        zip(data_frame[RETENTION_TIME].f64()?, data_frame[SIGNAL].f64()?)
+            .filter_map(|(retention_time, signal)| Some((retention_time?, signal?)))
+            .unzip(),
+    )
+}
+
+/// Maps a run retention time to the reference through the anchors (run,
+/// reference), piecewise linear inside and shifted by the edge anchor outside
+pub(crate) fn warp(anchors: &[(f64, f64)], retention_time: f64) -> f64 {
+    let (Some(first), Some(last)) = (anchors.first(), anchors.last()) else {
+        return retention_time;
+    };
+    if retention_time <= first.0 {
+        return retention_time + first.1 - first.0;
+    }
+    if retention_time >= last.0 {
+        return retention_time + last.1 - last.0;
+    }
+    let index = anchors.partition_point(|anchor| anchor.0 <= retention_time);
+    let (x0, y0) = anchors[index - 1];
+    let (x1, y1) = anchors[index];
+    y0 + (y1 - y0) * (retention_time - x0) / (x1 - x0)
+}
+
+/// Warps the `RETENTION_TIME` column (milliseconds) of a long frame
+pub(crate) fn apply(data_frame: &DataFrame, anchors: &[(f64, f64)]) -> PolarsResult<DataFrame> {
+    let mut data_frame = data_frame.clone();
+    let retention_time = data_frame[RETENTION_TIME].cast(&DataType::Int32)?;
+    let retention_time = retention_time.i32()?.apply_values(|retention_time| {
+        (warp(anchors, retention_time as f64 / MINUTES) * MINUTES).round() as _
+    });
+    data_frame.with_column(retention_time.into_series())?;
+    Ok(data_frame)
+}
+
+/// Anchor peak alignment
+///
+/// Local maxima higher than `min_height` (fraction of the maximum) are
+/// matched, highest reference peaks first, with the nearest run peak within
+/// `max_shift` (minutes) that keeps the anchors in order.
+pub(crate) fn anchors(
+    reference: (&[f64], &[f64]),
+    run: (&[f64], &[f64]),
+    min_height: f64,
+    max_shift: f64,
+) -> Vec<(f64, f64)> {
+    let reference_peaks = maxima(reference.1, min_height);
+    let run_peaks = maxima(run.1, min_height);
+    let mut order = reference_peaks.clone();
+    order.sort_by(|&left, &right| reference.1[right].total_cmp(&reference.1[left]));
+    let mut anchors = Vec::<(f64, f64)>::new();
+    for index in order {
+        let target = reference.0[index];
+        let Some(matched) = run_peaks
+            .iter()
+            .map(|&index| run.0[index])
+            .filter(|time| (time - target).abs() <= max_shift)
+            .filter(|&time| {
+                anchors.iter().all(|&(run, reference)| {
+                    (run < time && reference < target) || (run > time && reference > target)
+                })
+            })
+            .min_by(|left, right| (left - target).abs().total_cmp(&(right - target).abs()))
+        else {
+            continue;
+        };
+        anchors.push((matched, target));
+    }
+    anchors.sort_by(|left, right| left.0.total_cmp(&right.0));
+    anchors
+}
+
+/// Local maxima of the signal over five points higher than the fraction of
+/// the maximum
+fn maxima(y: &[f64], min_height: f64) -> Vec<usize> {
+    const WINDOW: usize = 2;
+    let max = y.iter().copied().fold(0.0, f64::max);
+    (0..y.len())
+        .filter(|&index| {
+            let start = index.saturating_sub(WINDOW);
+            let end = (index + WINDOW + 1).min(y.len());
+            y[index] > 0.0
+                && y[index] >= min_height * max
+                && y[start..end].iter().all(|&other| other <= y[index])
+        })
+        .collect()
+}
+
+/// Correlation optimized warping (Nielsen et al., 1998)
+///
+/// The run is split into `segments` equal segments whose boundaries move by up
+/// to `slack` points, the boundaries maximizing the sum of the Pearson
+/// correlations of the linearly resampled run segments with the reference
+/// segments are found by dynamic programming.
+pub(crate) fn correlation_optimized_warping(
+    reference: (&[f64], &[f64]),
+    run: (&[f64], &[f64]),
+    segments: usize,
+    slack: usize,
+) -> Vec<(f64, f64)> {
+    let (m, n) = (reference.1.len(), run.1.len());
+    if segments == 0 || m < 2 * segments + 1 || n < 2 * segments + 1 {
+        return Vec::new();
+    }
+    let reference_boundaries = (0..=segments)
+        .map(|index| index * (m - 1) / segments)
+        .collect::<Vec<_>>();
+    let run_boundaries = (0..=segments)
+        .map(|index| index * (n - 1) / segments)
+        .collect::<Vec<_>>();
+    // Positions, scores and previous positions of each boundary
+    let mut stages = vec![vec![(0, 0.0, 0)]];
+    for segment in 1..=segments {
+        let positions = if segment == segments {
+            n - 1..=n - 1
+        } else {
+            let boundary = run_boundaries[segment];
+            boundary.saturating_sub(slack)..=(boundary + slack).min(n - 2)
+        };
+        let reference_segment =
+            &reference.1[reference_boundaries[segment - 1]..=reference_boundaries[segment]];
+        let previous = &stages[segment - 1];
+        let stage = positions
+            .filter_map(|position| {
+                previous
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (start, ..))| position > start + 1)
+                    .map(|(index, &(start, score, _))| {
+                        let run_segment =
+                            resample(&run.1[start..=position], reference_segment.len());
+                        (index, score + correlation(reference_segment, &run_segment))
+                    })
+                    .max_by(|left, right| left.1.total_cmp(&right.1))
+                    .map(|(index, score)| (position, score, index))
+            })
+            .collect::<Vec<_>>();
+        if stage.is_empty() {
+            return Vec::new();
+        }
+        stages.push(stage);
+    }
+    // Backtrack from the fixed end
+    let mut anchors = Vec::with_capacity(segments + 1);
+    let mut index = 0;
+    for segment in (0..=segments).rev() {
+        let (position, _, previous) = stages[segment][index];
+        anchors.push((run.0[position], reference.0[reference_boundaries[segment]]));
+        index = previous;
+    }
+    anchors.reverse();
+    anchors
+}
+
+/// Linear resampling to `length` points
+fn resample(y: &[f64], length: usize) -> Vec<f64> {
+    if length < 2 || y.len() < 2 {
+        return vec![y.first().copied().unwrap_or_default(); length];
+    }
+    let step = (y.len() - 1) as f64 / (length - 1) as f64;
+    (0..length)
+        .map(|index| {
+            let x = index as f64 * step;
+            let left = (x.floor() as usize).min(y.len() - 2);
+            let fraction = x - left as f64;
+            y[left] + (y[left + 1] - y[left]) * fraction
+        })
+        .collect()
+}
+
+/// Pearson correlation, zero for constant signals
+fn correlation(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let (mut xy, mut xx, mut yy) = (0.0, 0.0, 0.0);
+    for (x, y) in zip(x, y) {
+        xy += (x - mean_x) * (y - mean_y);
+        xx += (x - mean_x) * (x - mean_x);
+        yy += (y - mean_y) * (y - mean_y);
+    }
+    if xx <= 0.0 || yy <= 0.0 {
+        return 0.0;
+    }
+    xy / (xx * yy).sqrt()
+}
@@ -0,0 +1,207 @@
+use crate::app::states::settings::{Algorithm, Baseline};
+use std::iter::zip;
+
+/// Estimates the baseline of a chromatogram sampled at (roughly) equal steps
+pub(crate) fn estimate(y: &[f64], baseline: &Baseline) -> Vec<f64> {
+    match baseline.algorithm {
+        Algorithm::None => vec![0.0; y.len()],
+        Algorithm::AsymmetricLeastSquares => asymmetric_least_squares(
+            y,
+            baseline.smoothness,
+            baseline.asymmetry,
+            baseline.iterations,
+        ),
+        Algorithm::Snip => snip(y, baseline.window),
+        Algorithm::RollingMinimum => rolling_minimum(y, baseline.window),
+    }
+}
+
+/// Asymmetric least squares (Eilers & Boelens, 2005)
+///
+/// Minimizes `Σ wᵢ(yᵢ - zᵢ)² + λ Σ(Δ²zᵢ)²`, where points above the baseline
+/// get the weight `p` and points below it `1 - p`.
+pub(crate) fn asymmetric_least_squares(
+    y: &[f64],
+    lambda: f64,
+    p: f64,
+    iterations: usize,
+) -> Vec<f64> {
+    let mut weights = vec![1.0; y.len()];
+    let mut z = y.to_vec();
+    for _ in 0..iterations.max(1) {
+        z = whittaker(y, &weights, lambda);
+        for (weight, (y, z)) in zip(&mut weights, zip(y, &z)) {
+            *weight = if y > z { p } else { 1.0 - p };
+        }
+    }
+    z
+}
+
+/// Weighted Whittaker smoother (Eilers, 2003)
+///
+/// Solves `(W + λDᵀD)z = Wy` for the second difference matrix `D`.
+pub(crate) fn whittaker(y: &[f64], weights: &[f64], lambda: f64) -> Vec<f64> {
+    let n = y.len();
+    if n < 3 {
+        return y.to_vec();
+    }
+    // Bands of DᵀD
+    let mut d0 = vec![0.0; n];
+    let mut d1 = vec![0.0; n];
+    let mut d2 = vec![0.0; n];
+    const C: [f64; 3] = [1.0, -2.0, 1.0];
+    for k in 0..n - 2 {
+        for i in 0..3 {
+            d0[k + i] += C[i] * C[i];
+        }
+        for i in 0..2 {
+            d1[k + i] += C[i] * C[i + 1];
+        }
+        d2[k] += C[0] * C[2];
+    }
+    let a0 = zip(weights, &d0)
+        .map(|(weight, d)| weight + lambda * d)
+        .collect::<Vec<_>>();
+    let a1 = d1.iter().map(|d| lambda * d).collect::<Vec<_>>();
+    let a2 = d2.iter().map(|d| lambda * d).collect::<Vec<_>>();
+    let b = zip(weights, y)
+        .map(|(weight, y)| weight * y)
+        .collect::<Vec<_>>();
+    pentadiagonal(&a0, &a1, &a2, &b)
+}
+
+/// Solves a symmetric positive definite pentadiagonal system by `LDLᵀ`
+///
+/// `a0` is the main diagonal, `a1[i]` = `A[i][i + 1]`, `a2[i]` = `A[i][i + 2]`.
+fn pentadiagonal(a0: &[f64], a1: &[f64], a2: &[f64], b: &[f64]) -> Vec<f64> {
+    let n = a0.len();
+    let mut d = vec![0.0; n];
+    let mut l1 = vec![0.0; n];
+    let mut l2 = vec![0.0; n];
+    for i in 0..n {
+        if i >= 2 {
+            l2[i] = a2[i - 2] / d[i - 2];
+        }
+        if i >= 1 {
+            let mut value = a1[i - 1];
+            if i >= 2 {
+                value -= l2[i] * d[i - 2] * l1[i - 1];
+            }
+            l1[i] = value / d[i - 1];
+        }
+        d[i] = a0[i];
+        if i >= 1 {
+            d[i] -= l1[i] * l1[i] * d[i - 1];
+        }
+        if i >= 2 {
+            d[i] -= l2[i] * l2[i] * d[i - 2];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for i in 0..n {
+        x[i] = b[i];
+        if i >= 1 {
+            x[i] -= l1[i] * x[i - 1];
+        }
+        if i >= 2 {
+            x[i] -= l2[i] * x[i - 2];
+        }
+    }
+    for (x, d) in zip(&mut x, &d) {
+        *x /= d;
+    }
+    for i in (0..n).rev() {
+        if i + 1 < n {
+            x[i] -= l1[i + 1] * x[i + 1];
+        }
+        if i + 2 < n {
+            x[i] -= l2[i + 2] * x[i + 2];
+        }
+    }
+    x
+}
+
+/// Statistics-sensitive non-linear iterative peak-clipping (Ryan et al.,
+/// 1988) on the LLS transformed signal
+pub(crate) fn snip(y: &[f64], window: usize) -> Vec<f64> {
+    let n = y.len();
+    let mut v = y
+        .iter()
+        .map(|&y| ((y.max(0.0) + 1.0).sqrt() + 1.0).ln().ln_1p())
+        .collect::<Vec<_>>();
+    for p in 1..=window.min(n / 2) {
+        let previous = v.clone();
+        for i in p..n - p {
+            v[i] = previous[i].min((previous[i - p] + previous[i + p]) / 2.0);
+        }
+    }
+    v.into_iter()
+        .map(|v| ((v.exp() - 1.0).exp() - 1.0).powi(2) - 1.0)
+        .collect()
+}
+
+/// Centered rolling minimum smoothed by a centered rolling mean
+pub(crate) fn rolling_minimum(y: &[f64], window: usize) -> Vec<f64> {
+    let n = y.len();
+    let range =
+        |i: usize| i.saturating_sub(window)..i.saturating_add(window).saturating_add(1).min(n);
+    let minimum = (0..n)
+        .map(|i| y[range(i)].iter().copied().fold(f64::INFINITY, f64::min))
+        .collect::<Vec<_>>();
+    (0..n)
+        .map(|i| {
+            let values = &minimum[range(i)];
+            values.iter().sum::<f64>() / values.len() as f64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(left: &[f64], right: &[f64], tolerance: f64) {
+        assert_eq!(left.len(), right.len());
+        for (left, right) in zip(left, right) {
+            assert!((left - right).abs() <= tolerance, "{left} != {right}");
+        }
+    }
+
+    /// Flat signal of `level` with a gaussian peak of `height` at the center
+    fn peak(level: f64, height: f64) -> Vec<f64> {
+        (0..201)
+            .map(|index| {
+                let x = index as f64 - 100.0;
+                level + height * (-x * x / (2.0 * 5.0 * 5.0)).exp()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flat() {
+        let y = vec![10.0; 100];
+        assert_close(&asymmetric_least_squares(&y, 1e5, 0.01, 10), &y, 1e-6);
+        assert_close(&snip(&y, 20), &y, 1e-9);
+        assert_close(&rolling_minimum(&y, 10), &y, 0.0);
+        assert_close(&rolling_minimum(&y, usize::MAX), &y, 0.0);
+        assert_close(&snip(&y, usize::MAX), &y, 1e-9);
+    }
+
+    #[test]
+    fn under_peak() {
+        let y = peak(10.0, 1000.0);
+        let flat = vec![10.0; y.len()];
+        assert_close(&asymmetric_least_squares(&y, 1e5, 0.001, 10), &flat, 5.0);
+        assert_close(&snip(&y, 30), &flat, 1e-3);
+        assert_close(&rolling_minimum(&y, 30), &flat, 1e-5);
+    }
+
+    #[test]
+    fn none() {
+        let baseline = Baseline {
+            algorithm: Algorithm::None,
+            ..Default::default()
+        };
+        assert_eq!(estimate(&peak(10.0, 1000.0), &baseline), vec![0.0; 201]);
+    }
+}
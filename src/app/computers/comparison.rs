@@ -0,0 +1,104 @@
+use crate::{
+    app::{
+        notifications,
+        states::settings::{Settings, Trace},
+    },
+    r#const::*,
+    utils::hash::HashedDataFrame,
+};
+use const_format::formatcp;
+use egui::{
+    emath::{Float, OrderedFloat},
+    util::cache::{ComputerMut, FrameCache},
+};
+use polars::prelude::*;
+use std::iter::zip;
+
+/// Comparison computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// Comparison computer
+///
+/// Extracts the total ion chromatogram or an extracted ion chromatogram from
+/// a frame grouped by retention time.
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let signal = match key.trace {
+            Trace::TotalIonChromatogram => col(formatcp!("_{SIGNAL}.{SUM}")),
+            Trace::ExtractedIonChromatogram => {
+                let mass_to_charge = element()
+                    .struct_()
+                    .field_by_name(MASS_TO_CHARGE)
+                    .cast(DataType::Float64);
+                col(MASS_SPECTRUM)
+                    .list()
+                    .eval(
+                        element().struct_().field_by_name(SIGNAL).filter(
+                            mass_to_charge
+                                .clone()
+                                .gt_eq(lit(key.mass_to_charge.0 - key.tolerance.0))
+                                .and(
+                                    mass_to_charge
+                                        .lt_eq(lit(key.mass_to_charge.0 + key.tolerance.0)),
+                                ),
+                        ),
+                    )
+                    .list()
+                    .sum()
+            }
+        };
+        let data_frame = key
+            .frame
+            .data_frame
+            .clone()
+            .lazy()
+            .select([
+                col(RETENTION_TIME),
+                signal.cast(DataType::Float64).alias(SIGNAL),
+            ])
+            .collect()?;
+        let points = zip(data_frame[RETENTION_TIME].f64()?, data_frame[SIGNAL].f64()?)
+            .filter_map(|(retention_time, signal)| Some([retention_time?, signal?]))
+            .collect();
+        Ok(Value { points })
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute comparison", error);
+            Value::default()
+        })
+    }
+}
+
+/// Comparison key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) frame: &'a HashedDataFrame,
+    pub(crate) trace: Trace,
+    pub(crate) mass_to_charge: OrderedFloat<f64>,
+    pub(crate) tolerance: OrderedFloat<f64>,
+}
+
+impl<'a> Key<'a> {
+    pub(crate) fn new(frame: &'a HashedDataFrame, settings: &Settings) -> Self {
+        Self {
+            frame,
+            trace: settings.comparison.trace,
+            mass_to_charge: settings.comparison.mass_to_charge.ord(),
+            tolerance: settings.comparison.tolerance.ord(),
+        }
+    }
+}
+
+/// Comparison value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    /// Retention time (minutes) and signal
+    pub(crate) points: Vec<[f64; 2]>,
+}
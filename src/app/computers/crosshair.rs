@@ -0,0 +1,109 @@
+use super::points::Value as PointsValue;
+use crate::app::notifications;
+use egui::{
+    emath::{Float, OrderedFloat},
+    util::cache::{ComputerMut, FrameCache},
+};
+use polars::prelude::*;
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Crosshair computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// Crosshair computer
+///
+/// Mass spectrum of a scan and ion chromatogram of a mass to charge bin of a
+/// long frame, the plots under the heat map.
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let scan = key.scan.0;
+        let mass_to_charge = key.mass_to_charge();
+        let tolerance = key.tolerance.0;
+        let spectrum = key
+            .points
+            .range([scan, scan])
+            .iter()
+            .map(|&[_, mass_to_charge, signal]| [mass_to_charge, signal])
+            .collect();
+        let mut signals = BTreeMap::<OrderedFloat<f64>, f64>::new();
+        for &[retention_time, other, signal] in key.points.points.iter() {
+            if (other - mass_to_charge).abs() <= tolerance {
+                *signals.entry(retention_time.ord()).or_default() += signal;
+            }
+        }
+        let chromatogram = signals
+            .into_iter()
+            .map(|(retention_time, signal)| [retention_time.0, signal])
+            .collect();
+        Ok(Value {
+            spectrum: Arc::new(spectrum),
+            chromatogram: Arc::new(chromatogram),
+        })
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute crosshair", error);
+            Value::default()
+        })
+    }
+}
+
+/// Crosshair key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) points: &'a PointsValue,
+    /// Retention time of the scan, minutes
+    pub(crate) scan: OrderedFloat<f64>,
+    /// Mass to charge bin, `2 * tolerance` wide
+    pub(crate) bin: i64,
+    pub(crate) tolerance: OrderedFloat<f64>,
+}
+
+impl<'a> Key<'a> {
+    /// Key of the scan nearest to the retention time (minutes) and the bin of
+    /// the mass to charge
+    pub(crate) fn new(
+        points: &'a PointsValue,
+        [retention_time, mass_to_charge]: [f64; 2],
+        tolerance: f64,
+    ) -> Self {
+        let scans = &points.scans;
+        let index = scans.partition_point(|&scan| scan < retention_time);
+        let scan = [index.checked_sub(1), Some(index)]
+            .into_iter()
+            .flatten()
+            .filter_map(|index| scans.get(index).copied())
+            .min_by(|left, right| {
+                (left - retention_time)
+                    .abs()
+                    .total_cmp(&(right - retention_time).abs())
+            })
+            .unwrap_or(retention_time);
+        Self {
+            points,
+            scan: scan.ord(),
+            bin: (mass_to_charge / (2.0 * tolerance)).round() as _,
+            tolerance: tolerance.ord(),
+        }
+    }
+
+    /// Mass to charge at the center of the bin
+    pub(crate) fn mass_to_charge(&self) -> f64 {
+        self.bin as f64 * 2.0 * self.tolerance.0
+    }
+}
+
+/// Crosshair value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    /// Mass to charge and signal
+    pub(crate) spectrum: Arc<Vec<[f64; 2]>>,
+    /// Retention time (minutes) and signal
+    pub(crate) chromatogram: Arc<Vec<[f64; 2]>>,
+}
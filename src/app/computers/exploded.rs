@@ -0,0 +1,86 @@
+use crate::{app::notifications, r#const::*, utils::hash::HashedDataFrame};
+use egui::util::cache::{ComputerMut, FrameCache};
+use itertools::izip;
+use polars::prelude::*;
+use std::sync::Arc;
+
+/// Milliseconds in a minute
+const MINUTES: f64 = 60_000.0;
+/// Number of signal levels
+pub(crate) const LEVELS: usize = 16;
+
+/// Exploded computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// Exploded computer
+///
+/// Splits the points of an exploded frame into signal levels, the level of a
+/// point is the square root of its signal relative to the maximum so that the
+/// minor ions stay visible.
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let retention_time = key.frame[RETENTION_TIME].cast(&DataType::Float64)?;
+        let mass_to_charge = key.frame[MASS_TO_CHARGE].cast(&DataType::Float64)?;
+        let signal = key.frame[SIGNAL].cast(&DataType::Float64)?;
+        let points = izip!(retention_time.f64()?, mass_to_charge.f64()?, signal.f64()?)
+            .filter_map(|(retention_time, mass_to_charge, signal)| {
+                Some((retention_time? / MINUTES, mass_to_charge?, signal?))
+            })
+            .filter(|&(.., signal)| signal > 0.0)
+            .collect::<Vec<_>>();
+        let max = points
+            .iter()
+            .map(|&(.., signal)| signal)
+            .fold(0.0, f64::max);
+        let mut levels = vec![Vec::new(); LEVELS];
+        for (retention_time, mass_to_charge, signal) in points {
+            let level = ((signal / max).sqrt() * LEVELS as f64) as usize;
+            levels[level.min(LEVELS - 1)].push([retention_time, mass_to_charge]);
+        }
+        Ok(Value {
+            levels: Arc::new(levels),
+            max,
+        })
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute exploded", error);
+            Value::default()
+        })
+    }
+}
+
+/// Exploded key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) frame: &'a HashedDataFrame,
+}
+
+impl<'a> Key<'a> {
+    pub(crate) fn new(frame: &'a HashedDataFrame) -> Self {
+        Self { frame }
+    }
+}
+
+/// Exploded value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    /// Retention time (minutes) and mass to charge of the points of each
+    /// signal level, lowest first, shared with the cache
+    pub(crate) levels: Arc<Vec<Vec<[f64; 2]>>>,
+    /// Maximal signal
+    pub(crate) max: f64,
+}
+
+impl Value {
+    /// Lowest signal of the level
+    pub(crate) fn signal(&self, level: usize) -> f64 {
+        self.max * (level as f64 / LEVELS as f64).powi(2)
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    app::{
+        notifications,
+        states::settings::{Ion, Settings},
+    },
+    r#const::*,
+    utils::hash::HashedDataFrame,
+};
+use egui::{
+    Color32,
+    util::cache::{ComputerMut, FrameCache},
+};
+use polars::prelude::*;
+use std::{collections::BTreeMap, iter::zip};
+
+/// Milliseconds in a minute
+const MINUTES: f64 = 60_000.0;
+
+/// Extraction computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// Extraction computer
+///
+/// Sums the `ExtractedIonChromatogram` lists of the frame grouped by mass to
+/// charge whose mass to charge is within the tolerance of each ion.
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    pub(crate) fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let mass_to_charge = key.frame[MASS_TO_CHARGE].cast(&DataType::Float64)?;
+        let mass_to_charge = mass_to_charge.f64()?;
+        let ion_chromatograms = key.frame["ExtractedIonChromatogram"].list()?;
+        let mut sum = BTreeMap::<i32, f64>::new();
+        let mut traces = Vec::with_capacity(key.ions.len() + 1);
+        for ion in key.ions {
+            let mut signals = BTreeMap::<i32, f64>::new();
+            for (mass_to_charge, ion_chromatogram) in zip(mass_to_charge, ion_chromatograms) {
+                let (Some(mass_to_charge), Some(ion_chromatogram)) =
+                    (mass_to_charge, ion_chromatogram)
+                else {
+                    continue;
+                };
+                if (mass_to_charge - ion.mass_to_charge).abs() > ion.tolerance {
+                    continue;
+                }
+                let ion_chromatogram = ion_chromatogram.struct_()?;
+                let retention_time = ion_chromatogram
+                    .field_by_name(RETENTION_TIME)?
+                    .cast(&DataType::Int32)?;
+                let signal = ion_chromatogram
+                    .field_by_name(SIGNAL)?
+                    .cast(&DataType::Float64)?;
+                for (retention_time, signal) in zip(retention_time.i32()?, signal.f64()?) {
+                    if let (Some(retention_time), Some(signal)) = (retention_time, signal) {
+                        *signals.entry(retention_time).or_default() += signal;
+                    }
+                }
+            }
+            if key.sum {
+                for (&retention_time, signal) in &signals {
+                    *sum.entry(retention_time).or_default() += signal;
+                }
+            }
+            traces.push(Trace {
+                name: ion.name(),
+                color: ion.color,
+                points: points(signals),
+            });
+        }
+        if key.sum {
+            traces.push(Trace {
+                name: "Sum".to_owned(),
+                color: Color32::GRAY,
+                points: points(sum),
+            });
+        }
+        Ok(Value { traces })
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute extraction", error);
+            Value::default()
+        })
+    }
+}
+
+/// Extraction key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) frame: &'a HashedDataFrame,
+    pub(crate) ions: &'a [Ion],
+    pub(crate) sum: bool,
+}
+
+impl<'a> Key<'a> {
+    pub(crate) fn new(frame: &'a HashedDataFrame, settings: &'a Settings) -> Self {
+        Self {
+            frame,
+            ions: &settings.extraction.ions,
+            sum: settings.extraction.sum,
+        }
+    }
+}
+
+/// Extraction value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    pub(crate) traces: Vec<Trace>,
+}
+
+/// Extracted ion chromatogram trace
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Trace {
+    pub(crate) name: String,
+    pub(crate) color: Color32,
+    /// Retention time (minutes) and signal
+    pub(crate) points: Vec<[f64; 2]>,
+}
+
+fn points(signals: BTreeMap<i32, f64>) -> Vec<[f64; 2]> {
+    signals
+        .into_iter()
+        .map(|(retention_time, signal)| [retention_time as f64 / MINUTES, signal])
+        .collect()
+}
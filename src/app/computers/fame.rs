@@ -0,0 +1,225 @@
+use super::search::mass_spectrum;
+use crate::{app::notifications, r#const::*, utils::hash::HashedDataFrame};
+use egui::{
+    emath::OrderedFloat,
+    util::cache::{ComputerMut, FrameCache},
+};
+use polars::prelude::*;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+/// Relative signal (percent of the base peak) of an ion to count as present
+const THRESHOLD: f64 = 1.0;
+/// Relative signal of a molecular ion candidate
+const MOLECULAR_ION_THRESHOLD: f64 = 0.3;
+/// Chain lengths of the molecular ion search
+const CARBONS: [u32; 2] = [4, 26];
+
+/// FAME computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// FAME computer
+///
+/// Classifies the mass spectra at the peak apexes of the frame grouped by
+/// retention time.
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    pub(crate) fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let retention_time = key.frame[RETENTION_TIME].f64()?;
+        let mut fames = BTreeMap::new();
+        for apex in key.apexes {
+            let Some(row_index) = retention_time
+                .iter()
+                .position(|retention_time| retention_time == Some(apex.0))
+            else {
+                continue;
+            };
+            if let Some(fame) = classify(&mass_spectrum(key.frame, row_index)?) {
+                fames.insert(row_index, fame);
+            }
+        }
+        Ok(Value { fames })
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute FAME", error);
+            Value::default()
+        })
+    }
+}
+
+/// FAME key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) frame: &'a HashedDataFrame,
+    /// Retention times of the peak apexes, minutes
+    pub(crate) apexes: &'a [OrderedFloat<f64>],
+}
+
+/// FAME value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    /// Proposed FAME by row of the frame
+    pub(crate) fames: BTreeMap<usize, Fame>,
+}
+
+/// Fatty acid methyl ester unsaturation class
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Class {
+    /// McLafferty ion m/z 74 and m/z 87
+    Saturated,
+    /// m/z 55 and 69
+    Monoene,
+    /// m/z 67 and 81
+    Diene,
+    /// m/z 79 and 91
+    Polyene,
+}
+
+impl Class {
+    const ALL: [Self; 4] = [Self::Saturated, Self::Monoene, Self::Diene, Self::Polyene];
+
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::Saturated => "Saturated",
+            Self::Monoene => "Monoene",
+            Self::Diene => "Diene",
+            Self::Polyene => "Polyene",
+        }
+    }
+
+    /// Characteristic ions
+    const fn ions(&self) -> [u32; 2] {
+        match self {
+            Self::Saturated => [74, 87],
+            Self::Monoene => [55, 69],
+            Self::Diene => [67, 81],
+            Self::Polyene => [79, 91],
+        }
+    }
+
+    /// Possible numbers of double bonds
+    const fn double_bonds(&self) -> [u32; 2] {
+        match self {
+            Self::Saturated => [0, 0],
+            Self::Monoene => [1, 1],
+            Self::Diene => [2, 2],
+            Self::Polyene => [3, 6],
+        }
+    }
+}
+
+/// Proposed fatty acid methyl ester
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Fame {
+    pub(crate) class: Class,
+    /// Carbon number and double bonds of the acid, if the molecular ion is
+    /// found
+    pub(crate) molecule: Option<[u32; 2]>,
+    /// Confidence from 0 to 1
+    pub(crate) confidence: f64,
+    /// Diagnostic ions used and their relative signals, percent
+    pub(crate) ions: Vec<(u32, f64)>,
+}
+
+impl Display for Fame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.molecule {
+            Some([carbons, double_bonds]) => write!(f, "C{carbons}:{double_bonds}"),
+            None => f.write_str(self.class.text()),
+        }
+    }
+}
+
+/// Classifies an electron ionization mass spectrum by the characteristic ions
+/// of the methyl esters
+pub(crate) fn classify(mass_spectrum: &[(f32, f64)]) -> Option<Fame> {
+    // Relative signals binned to nominal mass
+    let mut signals = BTreeMap::<u32, f64>::new();
+    for &(mass_to_charge, signal) in mass_spectrum {
+        if mass_to_charge.is_finite() && mass_to_charge > 0.0 && signal > 0.0 {
+            *signals.entry(mass_to_charge.round() as _).or_default() += signal;
+        }
+    }
+    let base = signals.values().copied().fold(0.0, f64::max);
+    if base <= 0.0 {
+        return None;
+    }
+    let signal = |mass_to_charge: u32| {
+        signals
+            .get(&mass_to_charge)
+            .map_or(0.0, |signal| 100.0 * signal / base)
+    };
+    // Class with the strongest characteristic ion pair, both ions present
+    let scores = Class::ALL.map(|class| {
+        let [first, second] = class.ions().map(signal);
+        let score = if first >= THRESHOLD && second >= THRESHOLD {
+            first + second
+        } else {
+            0.0
+        };
+        (class, score)
+    });
+    let total = scores.iter().map(|(_, score)| score).sum::<f64>();
+    let &(class, score) = scores
+        .iter()
+        .max_by(|left, right| left.1.total_cmp(&right.1))?;
+    if score <= 0.0 {
+        return None;
+    }
+    let mut ions = class
+        .ions()
+        .map(|mass_to_charge| (mass_to_charge, signal(mass_to_charge)))
+        .to_vec();
+    // Highest molecular ion `14n + 46 - 2d` of the class, confirmed by a
+    // fragment of it for the saturated (M - 31, M - 43) and monoenes (M - 32)
+    let [min, max] = class.double_bonds();
+    let molecule = (CARBONS[0]..=CARBONS[1])
+        .rev()
+        .flat_map(|carbons| (min..=max).map(move |double_bonds| [carbons, double_bonds]))
+        .filter_map(|[carbons, double_bonds]| {
+            let molecular_ion = (14 * carbons + 46).checked_sub(2 * double_bonds)?;
+            let relative = signal(molecular_ion);
+            (relative >= MOLECULAR_ION_THRESHOLD)
+                .then_some(([carbons, double_bonds], molecular_ion))
+        })
+        .max_by_key(|&(_, molecular_ion)| molecular_ion);
+    let mut confirmed = false;
+    if let Some((_, molecular_ion)) = molecule {
+        ions.push((molecular_ion, signal(molecular_ion)));
+        let fragments: &[u32] = match class {
+            Class::Saturated => &[31, 43],
+            Class::Monoene => &[32],
+            Class::Diene | Class::Polyene => &[],
+        };
+        for &loss in fragments {
+            let fragment = molecular_ion - loss;
+            let relative = signal(fragment);
+            if relative >= MOLECULAR_ION_THRESHOLD {
+                ions.push((fragment, relative));
+                confirmed = true;
+            }
+        }
+    }
+    // Share of the class among the characteristic ion pairs, lowered without
+    // the molecular ion
+    let mut confidence = score / total;
+    confidence *= match molecule {
+        Some(_) if confirmed => 1.0,
+        Some(_) => 0.8,
+        None => 0.5,
+    };
+    Some(Fame {
+        class,
+        molecule: molecule.map(|(molecule, _)| molecule),
+        confidence,
+        ions,
+    })
+}
@@ -0,0 +1,131 @@
+use super::points::{Value as PointsValue, widen};
+use crate::app::{
+    notifications,
+    states::settings::{Colormap, Settings},
+};
+use egui::{
+    Color32, ColorImage,
+    emath::{Float, OrderedFloat},
+    util::cache::{ComputerMut, FrameCache},
+};
+use polars::prelude::*;
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+/// Heat map computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// Heat map computer
+///
+/// Rasterizes the points of a long frame inside the bounds into a retention
+/// time × mass to charge image, the signal of the points falling into a bin is
+/// summed. The points come from the points computer, only the bounds change on
+/// pan and zoom.
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let [[left, bottom], [right, top]] = key.bounds.map(|point| point.map(|value| value.0));
+        let ([left, right], [bottom, top]) = (widen([left, right]), widen([bottom, top]));
+        let (width, height) = (key.resolution, key.resolution);
+        let mut bins = vec![0.0; width * height];
+        // Only the scans inside the bounds
+        for &[retention_time, mass_to_charge, signal] in key.points.range([left, right]) {
+            let x = (retention_time - left) / (right - left) * width as f64;
+            let y = (top - mass_to_charge) / (top - bottom) * height as f64;
+            if x < 0.0 || y < 0.0 || x >= width as f64 || y >= height as f64 {
+                continue;
+            }
+            bins[y as usize * width + x as usize] += signal;
+        }
+        let max = bins.iter().copied().fold(0.0, f64::max);
+        let pixels = bins
+            .iter()
+            .map(|&signal| {
+                if signal <= 0.0 {
+                    return Color32::TRANSPARENT;
+                }
+                let fraction = if key.logarithmic {
+                    signal.ln_1p() / max.ln_1p()
+                } else {
+                    signal / max
+                };
+                key.colormap.color(fraction as _)
+            })
+            .collect();
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        Ok(Value {
+            hash: hasher.finish(),
+            image: Arc::new(ColorImage::new([width, height], pixels)),
+            bounds: [[left, bottom], [right, top]],
+        })
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute heat map", error);
+            Value::default()
+        })
+    }
+}
+
+/// Heat map key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) points: &'a PointsValue,
+    /// Retention time (minutes) and mass to charge of the bottom left and the
+    /// top right corners
+    pub(crate) bounds: [[OrderedFloat<f64>; 2]; 2],
+    pub(crate) colormap: Colormap,
+    pub(crate) logarithmic: bool,
+    pub(crate) resolution: usize,
+}
+
+impl<'a> Key<'a> {
+    pub(crate) fn new(points: &'a PointsValue, bounds: [[f64; 2]; 2], settings: &Settings) -> Self {
+        Self {
+            points,
+            bounds: bounds.map(|point| point.map(Float::ord)),
+            colormap: settings.heat_map.colormap,
+            logarithmic: settings.heat_map.logarithmic,
+            resolution: settings.heat_map.resolution.max(1),
+        }
+    }
+}
+
+/// Heat map value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    /// Hash of the key, identifies the image
+    pub(crate) hash: u64,
+    pub(crate) image: Arc<ColorImage>,
+    pub(crate) bounds: [[f64; 2]; 2],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_bounds() -> PolarsResult<()> {
+        let points = PointsValue {
+            points: Arc::new(vec![[1.0, 50.0, 1.0]]),
+            scans: Arc::new(vec![1.0]),
+            ..Default::default()
+        };
+        let key = Key::new(&points, [[1.0, 50.0], [1.0, 50.0]], &Settings::default());
+        let value = Computer.try_compute(key)?;
+        assert_eq!(value.bounds, [[0.5, 49.5], [1.5, 50.5]]);
+        // The point falls into the center bin
+        let resolution = key.resolution;
+        let center = resolution / 2 * resolution + resolution / 2;
+        assert_ne!(value.image.pixels[center], Color32::TRANSPARENT);
+        Ok(())
+    }
+}
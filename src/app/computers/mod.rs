@@ -1,2 +1,15 @@
+pub(crate) mod alignment;
+pub(crate) mod baseline;
+pub(crate) mod comparison;
+pub(crate) mod crosshair;
+pub(crate) mod exploded;
+pub(crate) mod extraction;
+pub(crate) mod fame;
+pub(crate) mod heat_map;
+pub(crate) mod peaks;
 pub(crate) mod plot;
+pub(crate) mod points;
+pub(crate) mod search;
+pub(crate) mod smoothing;
+pub(crate) mod spectrum;
 pub(crate) mod table;
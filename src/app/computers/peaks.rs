@@ -0,0 +1,494 @@
+use crate::{
+    app::{
+        notifications,
+        states::settings::{Settings, Split},
+    },
+    r#const::*,
+    utils::hash::HashedDataFrame,
+};
+use const_format::formatcp;
+use egui::{
+    emath::{Float, OrderedFloat},
+    util::cache::{ComputerMut, FrameCache},
+};
+use polars::prelude::*;
+use std::iter::zip;
+
+/// Peaks computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// Peaks computer
+///
+/// Detects peaks on the total ion chromatogram (`_Signal.Sum` of the frame
+/// grouped by retention time) with a derivative state machine and integrates
+/// them above a piecewise linear baseline.
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    pub(crate) fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let (x, y) = chromatogram(key.frame)?;
+        let max = y.iter().copied().fold(0.0, f64::max);
+        if x.len() < 3 || max <= 0.0 {
+            return Ok(Value::default());
+        }
+        let derivative = derivative(&x, &y, key.window);
+        let segments = detect(
+            &y,
+            &derivative,
+            key.slope.0 * max,
+            key.min_height.0 * max,
+            key.min_width,
+        );
+        let mut peaks = integrate(&x, &y, &segments, key);
+        let total = peaks.iter().map(|peak| peak.area).sum::<f64>();
+        if total > 0.0 {
+            for peak in &mut peaks {
+                peak.percent = 100.0 * peak.area / total;
+            }
+        }
+        Ok(Value { peaks })
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute peaks", error);
+            Value::default()
+        })
+    }
+}
+
+/// Peaks key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) frame: &'a HashedDataFrame,
+    pub(crate) min_height: OrderedFloat<f64>,
+    pub(crate) min_width: usize,
+    pub(crate) skim: OrderedFloat<f64>,
+    pub(crate) slope: OrderedFloat<f64>,
+    pub(crate) split: Split,
+    pub(crate) window: usize,
+}
+
+impl<'a> Key<'a> {
+    pub(crate) fn new(frame: &'a HashedDataFrame, settings: &Settings) -> Self {
+        Self {
+            frame,
+            min_height: settings.peaks.min_height.ord(),
+            min_width: settings.peaks.min_width,
+            skim: settings.peaks.skim.ord(),
+            slope: settings.peaks.slope.ord(),
+            split: settings.peaks.split,
+            window: settings.peaks.window,
+        }
+    }
+}
+
+/// Peaks value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    pub(crate) peaks: Vec<Peak>,
+}
+
+/// Integrated peak
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Peak {
+    /// Retention time and signal of the apex
+    pub(crate) apex: [f64; 2],
+    /// Baseline start point
+    pub(crate) start: [f64; 2],
+    /// Baseline end point
+    pub(crate) end: [f64; 2],
+    /// Apex height above the baseline
+    pub(crate) height: f64,
+    /// Area above the baseline (signal × minutes)
+    pub(crate) area: f64,
+    /// Area percent of the total area
+    pub(crate) percent: f64,
+    /// Rider integrated by tangent skim off the preceding peak
+    pub(crate) skimmed: bool,
+    /// Chromatogram points between start and end
+    pub(crate) points: Vec<[f64; 2]>,
+}
+
+/// Retention time (minutes) and total signal of the frame grouped by
+/// retention time
+pub(crate) fn chromatogram(data_frame: &DataFrame) -> PolarsResult<(Vec<f64>, Vec<f64>)> {
+    let retention_time = data_frame[RETENTION_TIME].f64()?;
+    let signal = data_frame[formatcp!("_{SIGNAL}.{SUM}")].cast(&DataType::Float64)?;
+    Ok(zip(retention_time, signal.f64()?)
+        .filter_map(|(retention_time, signal)| Some((retention_time?, signal?)))
+        .unzip())
+}
+
+/// Least squares slope over `2 * window + 1` points
+fn derivative(x: &[f64], y: &[f64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    (0..x.len())
+        .map(|index| {
+            let start = index.saturating_sub(window);
+            let end = index.saturating_add(window).saturating_add(1).min(x.len());
+            let count = (end - start) as f64;
+            let mean_x = x[start..end].iter().sum::<f64>() / count;
+            let mean_y = y[start..end].iter().sum::<f64>() / count;
+            let mut covariance = 0.0;
+            let mut variance = 0.0;
+            for (x, y) in zip(&x[start..end], &y[start..end]) {
+                covariance += (x - mean_x) * (y - mean_y);
+                variance += (x - mean_x) * (x - mean_x);
+            }
+            if variance > 0.0 {
+                covariance / variance
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Detected peak boundaries (indices)
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    start: usize,
+    apex: usize,
+    end: usize,
+    /// Starts at the valley of the preceding peak
+    fused: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum State {
+    Baseline,
+    Up,
+    Down,
+    /// Descent flattened for a number of points, waiting for the baseline or
+    /// the next rise
+    Tail(usize),
+}
+
+fn detect(
+    y: &[f64],
+    derivative: &[f64],
+    slope: f64,
+    min_height: f64,
+    min_width: usize,
+) -> Vec<Segment> {
+    let argmax = |start: usize, end: usize| {
+        (start..=end)
+            .max_by(|&left, &right| y[left].total_cmp(&y[right]))
+            .unwrap_or(start)
+    };
+    let argmin = |start: usize, end: usize| {
+        (start..=end)
+            .min_by(|&left, &right| y[left].total_cmp(&y[right]))
+            .unwrap_or(start)
+    };
+    let mut segments = Vec::<Segment>::new();
+    let mut push = |segment: Segment| {
+        let apex = argmax(segment.start, segment.end);
+        // Fused only to a kept peak
+        let fused = segment.fused
+            && segments
+                .last()
+                .is_some_and(|last: &Segment| last.end == segment.start);
+        let segment = Segment {
+            apex,
+            fused,
+            ..segment
+        };
+        let height = y[apex] - y[segment.start].max(y[segment.end]);
+        if height >= min_height && segment.end - segment.start + 1 >= min_width {
+            segments.push(segment);
+        } else if segment.fused
+            && let Some(last) = segments.last_mut()
+            && last.end == segment.start
+        {
+            // Too small to stand alone, belongs to the preceding peak
+            last.end = segment.end;
+        }
+    };
+    let mut state = State::Baseline;
+    let mut start = 0;
+    let mut fused = false;
+    let mut last_end = 0;
+    for index in 0..y.len() {
+        let derivative = derivative[index];
+        state = match state {
+            State::Baseline if derivative > slope => {
+                start = argmin(last_end, index);
+                fused = false;
+                State::Up
+            }
+            State::Up if derivative < 0.0 => State::Down,
+            State::Down if derivative >= -slope => State::Tail(1),
+            State::Tail(_) if derivative < -slope => State::Down,
+            // Rising again before the baseline is reached
+            State::Tail(_) if derivative > slope => {
+                let apex = argmax(start, index);
+                let valley = argmin(apex, index);
+                push(Segment {
+                    start,
+                    apex,
+                    end: valley,
+                    fused,
+                });
+                last_end = valley;
+                start = valley;
+                fused = true;
+                State::Up
+            }
+            State::Tail(flat) if flat < min_width.max(1) => State::Tail(flat + 1),
+            State::Tail(_) => {
+                let apex = argmax(start, index);
+                let end = argmin(apex, index);
+                push(Segment {
+                    start,
+                    apex,
+                    end,
+                    fused,
+                });
+                last_end = end;
+                State::Baseline
+            }
+            state => state,
+        };
+    }
+    if let State::Up | State::Down | State::Tail(_) = state {
+        let end = y.len() - 1;
+        let apex = argmax(start, end);
+        push(Segment {
+            start,
+            apex,
+            end: argmin(apex, end),
+            fused,
+        });
+    }
+    segments
+}
+
+/// Straight line through two points
+fn line([x0, y0]: [f64; 2], [x1, y1]: [f64; 2]) -> impl Fn(f64) -> f64 {
+    move |x| {
+        if x1 == x0 {
+            y0
+        } else {
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        }
+    }
+}
+
+/// Trapezoidal area between the curve, limited by `ceiling`, and the
+/// baseline
+fn area(
+    x: &[f64],
+    start: usize,
+    end: usize,
+    baseline: &impl Fn(f64) -> f64,
+    ceiling: &impl Fn(usize) -> f64,
+) -> f64 {
+    (start..end)
+        .map(|index| {
+            let left = (ceiling(index) - baseline(x[index])).max(0.0);
+            let right = (ceiling(index + 1) - baseline(x[index + 1])).max(0.0);
+            (x[index + 1] - x[index]) * (left + right) / 2.0
+        })
+        .sum()
+}
+
+fn integrate(x: &[f64], y: &[f64], segments: &[Segment], key: Key) -> Vec<Peak> {
+    let point = |index: usize| [x[index], y[index]];
+    let curve = |index: usize| y[index];
+    let mut peaks = Vec::<Peak>::with_capacity(segments.len());
+    // Clusters of fused peaks share the baseline
+    let mut index = 0;
+    while index < segments.len() {
+        let mut last = index;
+        while last + 1 < segments.len() && segments[last + 1].fused {
+            last += 1;
+        }
+        let cluster = &segments[index..=last];
+        let common = line(
+            point(cluster[0].start),
+            point(cluster[cluster.len() - 1].end),
+        );
+        let boundary = |index: usize| match key.split {
+            Split::Drop => [x[index], common(x[index])],
+            Split::Valley => point(index),
+        };
+        // Index of the parent peak, its start and its curve from the start,
+        // limited by the skims of its riders
+        let mut parent: Option<(usize, usize, Vec<f64>)> = None;
+        for segment in cluster {
+            let baseline = line(boundary(segment.start), boundary(segment.end));
+            let height = y[segment.apex] - baseline(x[segment.apex]);
+            // Tangent skim a rider off the tail of the parent peak
+            if let Some((parent, parent_start, ceiling)) = &mut parent
+                && height < key.skim.0 * peaks[*parent].height
+            {
+                let parent_start = *parent_start;
+                let skim = line(point(segment.start), point(segment.end));
+                ceiling.extend((parent_start + ceiling.len()..=segment.end).map(curve));
+                for index in segment.start..=segment.end {
+                    ceiling[index - parent_start] = skim(x[index]).min(y[index]);
+                }
+                let ceiling = |index: usize| ceiling[index - parent_start];
+                let previous: &mut Peak = &mut peaks[*parent];
+                previous.end = boundary(segment.end);
+                let baseline = line(previous.start, previous.end);
+                previous.height = previous.apex[1] - baseline(previous.apex[0]);
+                // Recomputed over the whole parent, the baseline moved, the
+                // areas of the earlier riders stay skimmed off
+                previous.area = area(x, parent_start, segment.end, &baseline, &ceiling);
+                previous.points.extend(
+                    (segment.start + 1..=segment.end).map(|index| [x[index], ceiling(index)]),
+                );
+                peaks.push(Peak {
+                    apex: point(segment.apex),
+                    start: point(segment.start),
+                    end: point(segment.end),
+                    height: y[segment.apex] - skim(x[segment.apex]),
+                    area: area(x, segment.start, segment.end, &skim, &curve),
+                    percent: 0.0,
+                    skimmed: true,
+                    points: (segment.start..=segment.end).map(point).collect(),
+                });
+                continue;
+            }
+            parent = Some((
+                peaks.len(),
+                segment.start,
+                y[segment.start..=segment.end].to_vec(),
+            ));
+            peaks.push(Peak {
+                apex: point(segment.apex),
+                start: boundary(segment.start),
+                end: boundary(segment.end),
+                height,
+                area: area(x, segment.start, segment.end, &baseline, &curve),
+                percent: 0.0,
+                skimmed: false,
+                points: (segment.start..=segment.end).map(point).collect(),
+            });
+        }
+        index = last + 1;
+    }
+    peaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(frame: &HashedDataFrame, split: Split) -> Key<'_> {
+        let mut settings = Settings::default();
+        settings.peaks.skim = 0.5;
+        settings.peaks.split = split;
+        Key::new(frame, &settings)
+    }
+
+    fn segment(start: usize, apex: usize, end: usize, fused: bool) -> Segment {
+        Segment {
+            start,
+            apex,
+            end,
+            fused,
+        }
+    }
+
+    /// Trapezoidal area under the whole curve
+    fn total(x: &[f64], y: &[f64]) -> f64 {
+        area(x, 0, x.len() - 1, &|_| 0.0, &|index| y[index])
+    }
+
+    fn assert_close(left: f64, right: f64) {
+        assert!((left - right).abs() < 1e-9, "{left} != {right}");
+    }
+
+    #[test]
+    fn detect_gaussian() {
+        let x = (0..101)
+            .map(|index| index as f64 * 0.01)
+            .collect::<Vec<_>>();
+        let y = x
+            .iter()
+            .map(|x| (-(x - 0.5).powi(2) / (2.0 * 0.05f64.powi(2))).exp())
+            .collect::<Vec<_>>();
+        let segments = detect(&y, &derivative(&x, &y, 2), 0.1, 0.1, 3);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].apex, 50);
+        assert!(segments[0].start < 40 && segments[0].end > 60);
+    }
+
+    #[test]
+    fn derivative_window() {
+        // A window wider than the series fits a single line
+        let x = [0.0, 1.0, 2.0, 3.0];
+        let y = [1.0, 3.0, 5.0, 7.0];
+        assert_eq!(derivative(&x, &y, usize::MAX), [2.0; 4]);
+    }
+
+    #[test]
+    fn drop_split() {
+        let frame = HashedDataFrame::EMPTY;
+        let x = (0..9).map(|index| index as f64).collect::<Vec<_>>();
+        let y = [0.0, 4.0, 8.0, 4.0, 2.0, 4.0, 8.0, 4.0, 0.0];
+        let segments = [segment(0, 2, 4, false), segment(4, 6, 8, true)];
+        let peaks = integrate(&x, &y, &segments, key(&frame, Split::Drop));
+        assert_eq!(peaks.len(), 2);
+        // Perpendicular drop to the common baseline
+        assert_eq!(peaks[0].end, [4.0, 0.0]);
+        assert_eq!(peaks[1].start, [4.0, 0.0]);
+        assert_close(peaks[0].area, 17.0);
+        assert_close(peaks[1].area, 17.0);
+        assert_close(peaks[0].area + peaks[1].area, total(&x, &y));
+    }
+
+    #[test]
+    fn valley_split() {
+        let frame = HashedDataFrame::EMPTY;
+        let x = (0..9).map(|index| index as f64).collect::<Vec<_>>();
+        let y = [0.0, 4.0, 8.0, 4.0, 2.0, 4.0, 8.0, 4.0, 0.0];
+        let segments = [segment(0, 2, 4, false), segment(4, 6, 8, true)];
+        let peaks = integrate(&x, &y, &segments, key(&frame, Split::Valley));
+        // Baselines through the valley
+        assert_eq!(peaks[0].end, [4.0, 2.0]);
+        assert_close(peaks[0].height, 7.0);
+        assert_close(peaks[0].area, 13.0);
+        assert_close(peaks[1].area, 13.0);
+    }
+
+    #[test]
+    fn skimmed_rider() {
+        let frame = HashedDataFrame::EMPTY;
+        let x = (0..9).map(|index| index as f64).collect::<Vec<_>>();
+        let y = [0.0, 4.0, 8.0, 4.0, 2.0, 3.0, 2.0, 1.0, 0.0];
+        let segments = [segment(0, 2, 4, false), segment(4, 5, 8, true)];
+        let peaks = integrate(&x, &y, &segments, key(&frame, Split::Valley));
+        assert_eq!(peaks.len(), 2);
+        assert!(!peaks[0].skimmed);
+        assert!(peaks[1].skimmed);
+        // The parent extends under the rider to the end of the cluster
+        assert_eq!(peaks[0].end, [8.0, 0.0]);
+        assert_close(peaks[0].height, 8.0);
+        assert_close(peaks[0].area, 21.0);
+        // The rider is cut by the tangent from its start to its end
+        assert_close(peaks[1].height, 1.5);
+        assert_close(peaks[1].area, 3.0);
+        assert_close(peaks[0].area + peaks[1].area, total(&x, &y));
+    }
+
+    #[test]
+    fn unskimmed_rider() {
+        let frame = HashedDataFrame::EMPTY;
+        let x = (0..9).map(|index| index as f64).collect::<Vec<_>>();
+        let y = [0.0, 4.0, 8.0, 4.0, 2.0, 3.0, 2.0, 1.0, 0.0];
+        let segments = [segment(0, 2, 4, false), segment(4, 5, 8, true)];
+        let mut key = key(&frame, Split::Drop);
+        key.skim = OrderedFloat(0.0);
+        let peaks = integrate(&x, &y, &segments, key);
+        assert!(peaks.iter().all(|peak| !peak.skimmed));
+        assert_close(peaks[0].area + peaks[1].area, total(&x, &y));
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    app::states::settings::{BarSort, Settings, Sort},
+    app::{
+        notifications,
+        states::settings::{BarSort, Binning, Settings, Sort},
+    },
     r#const::*,
     utils::hash::HashedDataFrame,
 };
@@ -31,7 +34,6 @@ impl Computer {
         lazy_frame = sort(lazy_frame, key);
         // Compute
         let data_frame = lazy_frame.collect()?;
-        println!("data_frame: {:?}", data_frame.schema());
         let value = compute(&data_frame, key)?;
         Ok(value)
     }
@@ -39,7 +41,10 @@ impl Computer {
 
 impl ComputerMut<Key<'_>, Value> for Computer {
     fn compute(&mut self, key: Key<'_>) -> Value {
-        self.try_compute(key).expect("Compute plot")
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute plot", error);
+            Value::default()
+        })
     }
 }
 
@@ -49,6 +54,7 @@ pub struct Key<'a> {
     pub(crate) frame: &'a HashedDataFrame,
     pub(crate) bar_sort: BarSort,
     pub(crate) bar_width: OrderedFloat<f64>,
+    pub(crate) binning: Binning,
     pub(crate) normalize_signal: bool,
     pub(crate) peak_max: [bool; 2],
     pub(crate) peak_min: [bool; 2],
@@ -62,6 +68,7 @@ impl<'a> Key<'a> {
             frame,
             bar_sort: settings.plot.bar_sort,
             bar_width: settings.plot.bar_width.ord(),
+            binning: settings.binning,
             normalize_signal: settings.signal.normalize,
             peak_max: settings.peak_max,
             peak_min: settings.peak_min,
@@ -75,6 +82,8 @@ impl<'a> Key<'a> {
 #[derive(Clone, Debug, Default)]
 pub(crate) struct Value {
     pub(crate) bars: IndexMap<OrderedFloat<f32>, Vec<Bar>>,
+    pub(crate) baseline: Vec<[f64; 2]>,
+    pub(crate) corrected: Vec<[f64; 2]>,
     pub(crate) mass_spectrums: IndexMap<OrderedFloat<f64>, Vec<(f32, f64)>>,
     pub(crate) mean: Option<OrderedFloat<f64>>,
     pub(crate) median: Option<OrderedFloat<f64>>,
@@ -141,6 +150,7 @@ fn by_retention_time(data_frame: &DataFrame, key: Key) -> PolarsResult<Value> {
                 .or_insert_with(Vec::new)
                 .push((mass_to_charge, signal));
             let signal = signal as _;
+            let mass_to_charge = key.binning.bin(mass_to_charge);
             let offset = offsets.entry(retention_time.ord()).or_default();
             let mut bar = Bar::new(retention_time, signal)
                 .name(mass_to_charge.to_string())
@@ -156,6 +166,21 @@ fn by_retention_time(data_frame: &DataFrame, key: Key) -> PolarsResult<Value> {
                 .push(bar);
         }
     }
+    // Baseline
+    if let Ok(baseline) = data_frame.column(formatcp!("_{BASELINE}")) {
+        let retention_time = data_frame[RETENTION_TIME].f64()?;
+        let corrected = data_frame[formatcp!("_{SIGNAL}.{SUM}")].f64()?;
+        for (retention_time, (baseline, corrected)) in
+            zip(retention_time, zip(baseline.f64()?, corrected))
+        {
+            if let (Some(retention_time), Some(baseline), Some(corrected)) =
+                (retention_time, baseline, corrected)
+            {
+                value.baseline.push([retention_time, baseline]);
+                value.corrected.push([retention_time, corrected]);
+            }
+        }
+    }
     if key.stack {
         let retention_time = data_frame[RETENTION_TIME].f64()?;
         let rolling_mean = data_frame[formatcp!("_y.{ROLLING}.{MEAN}")].f64()?;
@@ -0,0 +1,158 @@
+use crate::{app::notifications, r#const::*, utils::hash::HashedDataFrame};
+use egui::util::cache::{ComputerMut, FrameCache};
+use itertools::izip;
+use polars::prelude::*;
+use std::{
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+/// Milliseconds in a minute
+const MINUTES: f64 = 60_000.0;
+
+/// Points computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// Points computer
+///
+/// Collects the points of a long frame sorted by retention time, with the
+/// retention times of its scans and its extent. Keyed on the frame only, the
+/// heat map rasterizes its visible region from them.
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let retention_time = key.frame[RETENTION_TIME].cast(&DataType::Float64)?;
+        let mass_to_charge = key.frame[MASS_TO_CHARGE].cast(&DataType::Float64)?;
+        let signal = key.frame[SIGNAL].cast(&DataType::Float64)?;
+        let mut points = izip!(retention_time.f64()?, mass_to_charge.f64()?, signal.f64()?)
+            .filter_map(|(retention_time, mass_to_charge, signal)| {
+                Some([retention_time? / MINUTES, mass_to_charge?, signal?])
+            })
+            .collect::<Vec<_>>();
+        points.sort_by(|left, right| left[0].total_cmp(&right[0]));
+        let mut scans = points
+            .iter()
+            .map(|&[retention_time, ..]| retention_time)
+            .collect::<Vec<_>>();
+        scans.dedup();
+        let extent = match (scans.first(), scans.last()) {
+            (Some(&left), Some(&right)) => {
+                let (bottom, top) = points.iter().fold(
+                    (f64::MAX, f64::MIN),
+                    |(bottom, top), &[_, mass_to_charge, _]| {
+                        (bottom.min(mass_to_charge), top.max(mass_to_charge))
+                    },
+                );
+                let ([left, right], [bottom, top]) = (widen([left, right]), widen([bottom, top]));
+                [[left, bottom], [right, top]]
+            }
+            _ => [[0.0; 2], [1.0; 2]],
+        };
+        Ok(Value {
+            hash: key.frame.hash,
+            points: Arc::new(points),
+            scans: Arc::new(scans),
+            extent,
+        })
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute points", error);
+            Value::default()
+        })
+    }
+}
+
+/// Points key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) frame: &'a HashedDataFrame,
+}
+
+impl<'a> Key<'a> {
+    pub(crate) fn new(frame: &'a HashedDataFrame) -> Self {
+        Self { frame }
+    }
+}
+
+/// Points value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    /// Hash of the frame, identifies the points
+    pub(crate) hash: u64,
+    /// Retention time (minutes), mass to charge and signal, sorted by
+    /// retention time
+    pub(crate) points: Arc<Vec<[f64; 3]>>,
+    /// Retention times of the scans, minutes, sorted
+    pub(crate) scans: Arc<Vec<f64>>,
+    /// Retention time (minutes) and mass to charge of the bottom left and the
+    /// top right corners enclosing all the points
+    pub(crate) extent: [[f64; 2]; 2],
+}
+
+impl Value {
+    /// Points with the retention time inside the range
+    pub(crate) fn range(&self, [start, end]: [f64; 2]) -> &[[f64; 3]] {
+        let start = self.points.partition_point(|point| point[0] < start);
+        let end = self.points.partition_point(|point| point[0] <= end);
+        &self.points[start..end.max(start)]
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+/// Range with an empty or a reversed span widened around its start
+pub(crate) fn widen([min, max]: [f64; 2]) -> [f64; 2] {
+    if max > min {
+        [min, max]
+    } else {
+        [min - 0.5, min + 0.5]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compute(data_frame: DataFrame) -> PolarsResult<Value> {
+        Computer.try_compute(Key::new(&HashedDataFrame::new(data_frame)?))
+    }
+
+    #[test]
+    fn sorted() -> PolarsResult<()> {
+        let value = compute(df! {
+            RETENTION_TIME => [120_000i32, 60_000, 60_000],
+            MASS_TO_CHARGE => [51.0f32, 50.0, 52.0],
+            SIGNAL => [3u16, 1, 2],
+        }?)?;
+        assert_eq!(*value.scans, [1.0, 2.0]);
+        assert_eq!(value.extent, [[1.0, 50.0], [2.0, 52.0]]);
+        assert_eq!(
+            value.range([1.0, 1.0]),
+            [[1.0, 50.0, 1.0], [1.0, 52.0, 2.0]]
+        );
+        assert_eq!(value.range([1.5, 3.0]), [[2.0, 51.0, 3.0]]);
+        assert!(value.range([3.0, 4.0]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn single_point() -> PolarsResult<()> {
+        let value = compute(df! {
+            RETENTION_TIME => [60_000i32],
+            MASS_TO_CHARGE => [50.0f32],
+            SIGNAL => [1u16],
+        }?)?;
+        assert_eq!(value.extent, [[0.5, 49.5], [1.5, 50.5]]);
+        Ok(())
+    }
+}
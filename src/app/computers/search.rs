@@ -0,0 +1,227 @@
+use crate::{
+    app::{
+        library::{Entry, Library},
+        notifications,
+        states::settings::Settings,
+    },
+    r#const::*,
+    utils::hash::HashedDataFrame,
+};
+use egui::{
+    emath::{Float, OrderedFloat},
+    util::cache::{ComputerMut, FrameCache},
+};
+use polars::prelude::*;
+use std::{collections::HashMap, iter::zip};
+
+/// Search computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// Search computer
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    pub(crate) fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let spectrum = mass_spectrum(key.frame, key.index)?;
+        let unknown = Weighted::new(spectrum.iter().copied(), key);
+        let mut hits = key
+            .library
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| score(&unknown, index, entry, key))
+            .collect::<Vec<_>>();
+        hits.sort_by(|left, right| right.forward.total_cmp(&left.forward));
+        hits.truncate(key.top);
+        Ok(Value { spectrum, hits })
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute search", error);
+            Value::default()
+        })
+    }
+}
+
+/// Search key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) frame: &'a HashedDataFrame,
+    pub(crate) index: usize,
+    pub(crate) library: &'a Library,
+    pub(crate) top: usize,
+    pub(crate) mass_to_charge_weight: OrderedFloat<f64>,
+    pub(crate) signal_weight: OrderedFloat<f64>,
+}
+
+impl<'a> Key<'a> {
+    pub(crate) fn new(
+        frame: &'a HashedDataFrame,
+        index: usize,
+        library: &'a Library,
+        settings: &Settings,
+    ) -> Self {
+        Self {
+            frame,
+            index,
+            library,
+            top: settings.search.top,
+            mass_to_charge_weight: settings.search.mass_to_charge_weight.ord(),
+            signal_weight: settings.search.signal_weight.ord(),
+        }
+    }
+}
+
+/// Search value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    /// Mass to charge and signal pairs of the searched spectrum
+    pub(crate) spectrum: Vec<(f32, f64)>,
+    pub(crate) hits: Vec<Hit>,
+}
+
+/// Search hit
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Hit {
+    /// Library entry index
+    pub(crate) index: usize,
+    /// Dot product match factor (0..=999)
+    pub(crate) forward: f64,
+    /// Reverse match factor, ignoring peaks absent in the library entry
+    /// (0..=999)
+    pub(crate) reverse: f64,
+}
+
+/// Mass spectrum of a row of the frame grouped by retention time
+pub(crate) fn mass_spectrum(data_frame: &DataFrame, index: usize) -> PolarsResult<Vec<(f32, f64)>> {
+    let Some(mass_spectrum) = data_frame[MASS_SPECTRUM].list()?.get_as_series(index) else {
+        polars_bail!(OutOfBounds: "{MASS_SPECTRUM} row {index}");
+    };
+    let mass_spectrum = mass_spectrum.struct_()?;
+    let signal = mass_spectrum
+        .field_by_name(SIGNAL)?
+        .cast(&DataType::Float64)?;
+    Ok(zip(
+        mass_spectrum.field_by_name(MASS_TO_CHARGE)?.f32()?,
+        signal.f64()?,
+    )
+    .filter_map(|(mass_to_charge, signal)| Some((mass_to_charge?, signal?)))
+    .collect())
+}
+
+/// Weighted spectrum binned to nominal mass
+struct Weighted {
+    weights: HashMap<u32, f64>,
+    norm: f64,
+}
+
+impl Weighted {
+    fn new(peaks: impl IntoIterator<Item = (f32, f64)>, key: Key) -> Self {
+        let mut signals = HashMap::<u32, f64>::new();
+        for (mass_to_charge, signal) in peaks {
+            if mass_to_charge > 0.0 && signal > 0.0 {
+                *signals.entry(mass_to_charge.round() as _).or_default() += signal;
+            }
+        }
+        let weights = signals
+            .into_iter()
+            .map(|(mass_to_charge, signal)| (mass_to_charge, weight(mass_to_charge, signal, key)))
+            .collect::<HashMap<_, _>>();
+        let norm = weights.values().map(|weight| weight * weight).sum();
+        Self { weights, norm }
+    }
+}
+
+fn weight(mass_to_charge: u32, signal: f64, key: Key) -> f64 {
+    (mass_to_charge as f64).powf(key.mass_to_charge_weight.0) * signal.powf(key.signal_weight.0)
+}
+
+fn score(unknown: &Weighted, index: usize, entry: &Entry, key: Key) -> Option<Hit> {
+    let library = Weighted::new(
+        entry
+            .peaks
+            .iter()
+            .map(|&(mass_to_charge, signal)| (mass_to_charge, signal as _)),
+        key,
+    );
+    if unknown.norm == 0.0 || library.norm == 0.0 {
+        return None;
+    }
+    let mut product = 0.0;
+    let mut reverse_norm = 0.0;
+    for (mass_to_charge, weight) in &library.weights {
+        if let Some(unknown) = unknown.weights.get(mass_to_charge) {
+            product += weight * unknown;
+            reverse_norm += unknown * unknown;
+        }
+    }
+    if product == 0.0 {
+        return None;
+    }
+    let forward = 999.0 * product * product / (unknown.norm * library.norm);
+    let reverse = 999.0 * product * product / (reverse_norm * library.norm);
+    Some(Hit {
+        index,
+        forward,
+        reverse,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(unknown: &[(f32, f64)], library: &[(f32, f32)]) -> Option<Hit> {
+        let frame = HashedDataFrame::EMPTY;
+        let entries = Library::default();
+        let key = Key::new(&frame, 0, &entries, &Settings::default());
+        let entry = Entry {
+            peaks: library.to_vec(),
+            ..Default::default()
+        };
+        score(&Weighted::new(unknown.iter().copied(), key), 0, &entry, key)
+    }
+
+    #[test]
+    fn identical() {
+        let hit = hit(
+            &[(41.0, 100.0), (43.0, 999.0), (58.0, 120.0)],
+            &[(41.0, 100.0), (43.0, 999.0), (58.0, 120.0)],
+        )
+        .unwrap();
+        assert!((hit.forward - 999.0).abs() < 1e-9);
+        assert!((hit.reverse - 999.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn scaled() {
+        // Binned to nominal mass and independent of the signal scale
+        let hit = hit(
+            &[(40.8, 50.0), (41.2, 50.0), (43.0, 999.0)],
+            &[(41.0, 10.0), (43.0, 99.9)],
+        )
+        .unwrap();
+        assert!((hit.forward - 999.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn extra_peak() {
+        let hit = hit(
+            &[(41.0, 100.0), (43.0, 999.0), (57.0, 500.0)],
+            &[(41.0, 100.0), (43.0, 999.0)],
+        )
+        .unwrap();
+        assert!(hit.forward < 999.0);
+        assert!((hit.reverse - 999.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn disjoint() {
+        assert!(hit(&[(41.0, 100.0)], &[(43.0, 100.0)]).is_none());
+        assert!(hit(&[], &[(43.0, 100.0)]).is_none());
+    }
+}
@@ -0,0 +1,201 @@
+use super::baseline::whittaker;
+use crate::app::states::settings::{Smoother, Smoothing};
+
+/// Smooths a chromatogram sampled at (roughly) equal steps
+pub(crate) fn smooth(y: &[f64], smoothing: &Smoothing) -> Vec<f64> {
+    match smoothing.smoother {
+        Smoother::None => y.to_vec(),
+        Smoother::SavitzkyGolay => savitzky_golay(y, smoothing.window, smoothing.order),
+        Smoother::Gaussian => gaussian(y, smoothing.sigma),
+        Smoother::Whittaker => whittaker(y, &vec![1.0; y.len()], smoothing.lambda),
+    }
+}
+
+/// Savitzky–Golay filter over `2 * window + 1` points
+///
+/// Edge points are evaluated on the polynomial fitted to the first and last
+/// full windows. The window is clamped to the series length.
+pub(crate) fn savitzky_golay(y: &[f64], window: usize, order: usize) -> Vec<f64> {
+    let n = y.len();
+    let window = window.min(n.saturating_sub(1) / 2);
+    let size = 2 * window + 1;
+    if window == 0 || order >= size {
+        return y.to_vec();
+    }
+    // Coefficients evaluating the fit at each window offset
+    let coefficients = (0..size)
+        .map(|offset| coefficients(window, order, offset as f64 - window as f64))
+        .collect::<Vec<_>>();
+    let convolve = |start: usize, coefficients: &[f64]| {
+        coefficients
+            .iter()
+            .zip(&y[start..start + size])
+            .map(|(coefficient, y)| coefficient * y)
+            .sum::<f64>()
+    };
+    (0..n)
+        .map(|index| {
+            if index < window {
+                convolve(0, &coefficients[index])
+            } else if index + window >= n {
+                convolve(n - size, &coefficients[index + size - n])
+            } else {
+                convolve(index - window, &coefficients[window])
+            }
+        })
+        .collect()
+}
+
+/// Least squares weights of the window points for the polynomial value at
+/// `position`: `e(position)ᵀ (AᵀA)⁻¹ Aᵀ`
+fn coefficients(window: usize, order: usize, position: f64) -> Vec<f64> {
+    let columns = order + 1;
+    let positions = (-(window as isize)..=window as isize).map(|position| position as f64);
+    let powers = |x: f64| (0..columns).map(move |power| x.powi(power as _));
+    // AᵀA
+    let mut normal = vec![vec![0.0; columns]; columns];
+    for x in positions.clone() {
+        for (row, left) in powers(x).enumerate() {
+            for (column, right) in powers(x).enumerate() {
+                normal[row][column] += left * right;
+            }
+        }
+    }
+    // (AᵀA)⁻¹ e(position), AᵀA is symmetric
+    let solution = solve(normal, powers(position).collect());
+    positions
+        .map(|x| {
+            powers(x)
+                .zip(&solution)
+                .map(|(power, value)| power * value)
+                .sum()
+        })
+        .collect()
+}
+
+/// Gaussian elimination with partial pivoting
+fn solve(mut matrix: Vec<Vec<f64>>, mut vector: Vec<f64>) -> Vec<f64> {
+    let n = vector.len();
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|&left, &right| {
+                matrix[left][column]
+                    .abs()
+                    .total_cmp(&matrix[right][column].abs())
+            })
+            .unwrap_or(column);
+        matrix.swap(column, pivot);
+        vector.swap(column, pivot);
+        for row in column + 1..n {
+            let factor = matrix[row][column] / matrix[column][column];
+            for index in column..n {
+                matrix[row][index] -= factor * matrix[column][index];
+            }
+            vector[row] -= factor * vector[column];
+        }
+    }
+    for row in (0..n).rev() {
+        let sum = (row + 1..n)
+            .map(|column| matrix[row][column] * vector[column])
+            .sum::<f64>();
+        vector[row] = (vector[row] - sum) / matrix[row][row];
+    }
+    vector
+}
+
+/// Gaussian kernel with the standard deviation `sigma` (points), truncated at
+/// 3σ, or the series length, and renormalized at the edges
+pub(crate) fn gaussian(y: &[f64], sigma: f64) -> Vec<f64> {
+    if sigma <= 0.0 {
+        return y.to_vec();
+    }
+    let radius = ((3.0 * sigma).ceil() as usize).min(y.len());
+    let kernel = (0..=2 * radius)
+        .map(|index| {
+            let x = index as f64 - radius as f64;
+            (-x * x / (2.0 * sigma * sigma)).exp()
+        })
+        .collect::<Vec<_>>();
+    let n = y.len();
+    (0..n)
+        .map(|index| {
+            let start = index.saturating_sub(radius);
+            let end = (index + radius + 1).min(n);
+            let mut sum = 0.0;
+            let mut weight = 0.0;
+            for (position, y) in (start..end).zip(&y[start..end]) {
+                let kernel = kernel[position + radius - index];
+                sum += kernel * y;
+                weight += kernel;
+            }
+            sum / weight
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::iter::zip;
+
+    fn assert_close(left: &[f64], right: &[f64], tolerance: f64) {
+        assert_eq!(left.len(), right.len());
+        for (left, right) in zip(left, right) {
+            assert!((left - right).abs() <= tolerance, "{left} != {right}");
+        }
+    }
+
+    #[test]
+    fn constant() {
+        let y = vec![5.0; 50];
+        assert_close(&savitzky_golay(&y, 3, 2), &y, 1e-9);
+        assert_close(&gaussian(&y, 2.0), &y, 1e-9);
+        assert_close(&whittaker(&y, &vec![1.0; y.len()], 100.0), &y, 1e-9);
+    }
+
+    #[test]
+    fn whittaker_line() {
+        // A straight line has no second differences
+        let y = (0..50)
+            .map(|index| 2.0 + 0.5 * index as f64)
+            .collect::<Vec<_>>();
+        assert_close(&whittaker(&y, &vec![1.0; y.len()], 1e6), &y, 1e-6);
+    }
+
+    #[test]
+    fn savitzky_golay_polynomial() {
+        // Polynomials up to the order are fitted exactly, edges included
+        let y = (0..30)
+            .map(|index| {
+                let x = index as f64;
+                1.0 - 2.0 * x + 0.25 * x * x
+            })
+            .collect::<Vec<_>>();
+        assert_close(&savitzky_golay(&y, 4, 2), &y, 1e-6);
+    }
+
+    #[test]
+    fn noise() {
+        // Alternating noise around a level is averaged out
+        let y = (0..60)
+            .map(|index| 10.0 + if index % 2 == 0 { 1.0 } else { -1.0 })
+            .collect::<Vec<_>>();
+        let level = vec![10.0; y.len()];
+        let inner = 10..50;
+        assert_close(
+            &savitzky_golay(&y, 5, 2)[inner.clone()],
+            &level[inner.clone()],
+            0.2,
+        );
+        assert_close(&gaussian(&y, 3.0)[inner.clone()], &level[inner], 0.01);
+    }
+
+    #[test]
+    fn short() {
+        // The window is clamped to the three points, fitted exactly
+        let y = [1.0, 3.0, 2.0];
+        assert_close(&savitzky_golay(&y, usize::MAX / 2, 2), &y, 1e-9);
+        assert_close(&gaussian(&y, f64::MAX), &[2.0; 3], 1e-9);
+        assert_eq!(gaussian(&y, 0.0), y);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    app::{
+        notifications,
+        selection::Selection,
+        states::settings::{Binning, Settings},
+    },
+    r#const::*,
+    utils::hash::HashedDataFrame,
+};
+use egui::{
+    emath::{Float, OrderedFloat},
+    util::cache::{ComputerMut, FrameCache},
+};
+use itertools::izip;
+use polars::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Milliseconds in a minute
+const MINUTES: f64 = 60_000.0;
+
+/// Spectrum computed
+pub(crate) type Computed = FrameCache<Value, Computer>;
+
+/// Spectrum computer
+///
+/// Averages the binned mass spectra of the scans inside the selection, the
+/// scan nearest to a single retention time. The mean spectrum of the flanking
+/// regions is subtracted when enabled.
+#[derive(Default)]
+pub(crate) struct Computer;
+
+impl Computer {
+    fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
+        let points = points(key.frame, key.binning)?;
+        let retention_times = points
+            .iter()
+            .map(|&(retention_time, ..)| retention_time.ord())
+            .collect::<BTreeSet<_>>();
+        let [start, end] = [key.start.0, key.end.0];
+        let (start, end) = if start < end {
+            (start, end)
+        } else {
+            // Nearest scan
+            let Some(nearest) = retention_times
+                .iter()
+                .map(|retention_time| retention_time.0)
+                .min_by(|left, right| (left - start).abs().total_cmp(&(right - start).abs()))
+            else {
+                return Ok(Value::default());
+            };
+            (nearest, nearest)
+        };
+        let selected = mean(&points, &retention_times, |time| {
+            time >= start && time <= end
+        });
+        let mut value = Value {
+            points: Vec::with_capacity(selected.0.len()),
+            scans: selected.1,
+            background: 0,
+        };
+        let background = if key.subtraction {
+            let flank = key.flank.0;
+            mean(&points, &retention_times, |time| {
+                (time >= start - flank && time < start) || (time > end && time <= end + flank)
+            })
+        } else {
+            Default::default()
+        };
+        value.background = background.1;
+        for (mass_to_charge, signal) in selected.0 {
+            let signal = signal
+                - background
+                    .0
+                    .get(&mass_to_charge)
+                    .copied()
+                    .unwrap_or_default();
+            if signal > 0.0 {
+                value.points.push([mass_to_charge.0 as _, signal]);
+            }
+        }
+        Ok(value)
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute spectrum", error);
+            Value::default()
+        })
+    }
+}
+
+/// Spectrum key
+#[derive(Clone, Copy, Hash, Debug)]
+pub struct Key<'a> {
+    pub(crate) frame: &'a HashedDataFrame,
+    pub(crate) start: OrderedFloat<f64>,
+    pub(crate) end: OrderedFloat<f64>,
+    pub(crate) binning: Binning,
+    pub(crate) subtraction: bool,
+    pub(crate) flank: OrderedFloat<f64>,
+}
+
+impl<'a> Key<'a> {
+    pub(crate) fn new(
+        frame: &'a HashedDataFrame,
+        selection: Selection,
+        settings: &Settings,
+    ) -> Self {
+        Self {
+            frame,
+            start: selection.start.ord(),
+            end: selection.end.ord(),
+            binning: settings.binning,
+            subtraction: settings.spectrum.subtraction,
+            flank: settings.spectrum.flank.ord(),
+        }
+    }
+}
+
+/// Spectrum value
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Value {
+    /// Mass to charge and mean signal
+    pub(crate) points: Vec<[f64; 2]>,
+    /// Number of the averaged scans
+    pub(crate) scans: usize,
+    /// Number of the background scans
+    pub(crate) background: usize,
+}
+
+/// Mean binned spectrum and number of the scans whose retention time matches
+fn mean(
+    points: &[(f64, f32, f64)],
+    retention_times: &BTreeSet<OrderedFloat<f64>>,
+    filter: impl Fn(f64) -> bool,
+) -> (BTreeMap<OrderedFloat<f32>, f64>, usize) {
+    let scans = retention_times
+        .iter()
+        .filter(|retention_time| filter(retention_time.0))
+        .count();
+    let mut spectrum = BTreeMap::<OrderedFloat<f32>, f64>::new();
+    if scans == 0 {
+        return (spectrum, 0);
+    }
+    for &(retention_time, mass_to_charge, signal) in points {
+        if filter(retention_time) {
+            *spectrum.entry(mass_to_charge.ord()).or_default() += signal / scans as f64;
+        }
+    }
+    (spectrum, scans)
+}
+
+/// Retention time (minutes), binned mass to charge and signal of the points
+fn points(data_frame: &DataFrame, binning: Binning) -> PolarsResult<Vec<(f64, f32, f64)>> {
+    let retention_time = data_frame[RETENTION_TIME].cast(&DataType::Float64)?;
+    let mass_to_charge = data_frame[MASS_TO_CHARGE].cast(&DataType::Float32)?;
+    let signal = data_frame[SIGNAL].cast(&DataType::Float64)?;
+    Ok(
+        izip!(retention_time.f64()?, mass_to_charge.f32()?, signal.f64()?)
+            .filter_map(|(retention_time, mass_to_charge, signal)| {
+                Some((
+                    retention_time? / MINUTES,
+                    binning.bin(mass_to_charge?),
+                    signal?,
+                ))
+            })
+            .collect(),
+    )
+}
@@ -1,5 +1,11 @@
 use crate::{
-    app::states::settings::{Settings, Sort, TimeUnits},
+    app::{
+        computers::{baseline::estimate, smoothing},
+        notifications,
+        states::settings::{
+            Algorithm, Baseline, Binning, Settings, Smoother, Smoothing, Sort, TimeUnits,
+        },
+    },
     r#const::*,
     utils::hash::HashedDataFrame,
 };
@@ -8,7 +14,7 @@ use egui::util::cache::{ComputerMut, FrameCache};
 use polars::{frame::DataFrame, prelude::*};
 use polars_ext::column;
 use std::hash::{Hash, Hasher};
-use tracing::{error, trace, warn};
+use tracing::trace;
 use uom::si::{
     f64::Time,
     time::{millisecond, minute, second},
@@ -23,9 +29,8 @@ pub(crate) type Computed = FrameCache<Value, Computer>;
 #[derive(Default)]
 pub(crate) struct Computer;
 
-impl ComputerMut<Key<'_>, Value> for Computer {
-    fn compute(&mut self, key: Key<'_>) -> Value {
-        error!(?key.frame.data_frame);
+impl Computer {
+    pub(crate) fn try_compute(&mut self, key: Key<'_>) -> PolarsResult<Value> {
         let mut lazy_frame = key.frame.data_frame.clone().lazy();
         // Filter nulls
         if key.filter_null {
@@ -36,15 +41,34 @@ impl ComputerMut<Key<'_>, Value> for Computer {
         if key.normalize_signal {
             lazy_frame = lazy_frame.with_column(col(SIGNAL).cast(DataType::Float64) / max(SIGNAL));
         }
+        // Exploded frame keeps one row per point
+        if key.explode {
+            let data_frame = exploded(lazy_frame, key).collect()?;
+            trace!(?data_frame);
+            return HashedDataFrame::new(data_frame);
+        }
         // Compute
-        lazy_frame = compute(lazy_frame, key);
-        println!("lazy_frame gg: {}", lazy_frame.clone().collect().unwrap());
-        lazy_frame = rolling(lazy_frame, key);
-        println!("lazy_frame gg1: {}", lazy_frame.clone().collect().unwrap());
-        lazy_frame = filter(lazy_frame, key);
-        let data_frame = lazy_frame.collect().unwrap();
+        lazy_frame = compute(lazy_frame, key)?;
+        lazy_frame = smooth(lazy_frame, key)?;
+        lazy_frame = baseline(lazy_frame, key)?;
+        // Rolling statistics and peak filter use the total signal of the frame
+        // grouped by retention time
+        if key.sort == Sort::RetentionTime {
+            lazy_frame = rolling(lazy_frame, key);
+            lazy_frame = filter(lazy_frame, key);
+        }
+        let data_frame = lazy_frame.collect()?;
         trace!(?data_frame);
-        HashedDataFrame::new(data_frame).unwrap()
+        HashedDataFrame::new(data_frame)
+    }
+}
+
+impl ComputerMut<Key<'_>, Value> for Computer {
+    fn compute(&mut self, key: Key<'_>) -> Value {
+        self.try_compute(key).unwrap_or_else(|error| {
+            notifications::error("Compute table", error);
+            Value::default()
+        })
     }
 }
 
@@ -52,12 +76,15 @@ impl ComputerMut<Key<'_>, Value> for Computer {
 #[derive(Clone, Copy, Hash, Debug)]
 pub struct Key<'a> {
     pub(crate) frame: &'a HashedDataFrame,
+    pub(crate) baseline: Baseline,
+    pub(crate) binning: Binning,
     pub(crate) explode: bool,
     pub(crate) filter_null: bool,
     pub(crate) min_periods: usize,
     pub(crate) normalize_signal: bool,
     pub(crate) peak_max: [bool; 2],
     pub(crate) peak_min: [bool; 2],
+    pub(crate) smoothing: Smoothing,
     pub(crate) sort: Sort,
     pub(crate) window_size: usize,
 }
@@ -66,12 +93,15 @@ impl<'a> Key<'a> {
     pub(crate) fn new(frame: &'a HashedDataFrame, settings: &Settings) -> Self {
         Self {
             frame,
+            baseline: settings.baseline,
+            binning: settings.binning,
             explode: settings.explode,
             filter_null: settings.filter_null,
             min_periods: settings.min_periods,
             normalize_signal: settings.signal.normalize,
             peak_max: settings.peak_max,
             peak_min: settings.peak_min,
+            smoothing: settings.smoothing,
             sort: settings.sort,
             window_size: settings.window_size,
         }
@@ -81,63 +111,135 @@ impl<'a> Key<'a> {
 /// Table value
 type Value = HashedDataFrame;
 
-fn compute(lazy_frame: LazyFrame, key: Key) -> LazyFrame {
+/// Points sorted by the grouping column first
+fn exploded(lazy_frame: LazyFrame, key: Key) -> LazyFrame {
     match key.sort {
-        Sort::RetentionTime => retention_time(lazy_frame, key),
+        Sort::RetentionTime => {
+            lazy_frame.sort([RETENTION_TIME, MASS_TO_CHARGE], Default::default())
+        }
+        Sort::MassToCharge => lazy_frame.sort([MASS_TO_CHARGE, RETENTION_TIME], Default::default()),
+    }
+}
+
+fn compute(lazy_frame: LazyFrame, key: Key) -> PolarsResult<LazyFrame> {
+    match key.sort {
+        Sort::RetentionTime => Ok(retention_time(lazy_frame)),
         Sort::MassToCharge => mass_to_charge(lazy_frame, key),
     }
 }
 
-fn mass_to_charge(mut lazy_frame: LazyFrame, key: Key) -> LazyFrame {
+fn mass_to_charge(mut lazy_frame: LazyFrame, key: Key) -> PolarsResult<LazyFrame> {
     trace!(lazy_data_frame =? lazy_frame.clone().collect());
-    lazy_frame = lazy_frame
+    // Bin mass to charge
+    let mut data_frame = lazy_frame.collect()?;
+    let mass_to_charge = data_frame[MASS_TO_CHARGE].cast(&DataType::Float32)?;
+    let mass_to_charge = mass_to_charge
+        .f32()?
+        .apply_values(|mass_to_charge| key.binning.bin(mass_to_charge));
+    data_frame.with_column(mass_to_charge.into_series())?;
+    // Retention times of the scans, the grid of the smoothed chromatograms
+    let retention_time = data_frame[RETENTION_TIME].cast(&DataType::Int32)?;
+    let mut scans = retention_time.i32()?.iter().flatten().collect::<Vec<_>>();
+    scans.sort_unstable();
+    scans.dedup();
+    lazy_frame = data_frame
+        .lazy()
         .sort([RETENTION_TIME], Default::default())
-        .group_by([col(MASS_TO_CHARGE).round(2, RoundMode::HalfToEven)])
+        .group_by([col(MASS_TO_CHARGE)])
         .agg([as_struct(vec![col(RETENTION_TIME), col(SIGNAL)]).alias("ExtractedIonChromatogram")]);
-    if !key.explode {
-        lazy_frame = lazy_frame.with_columns([
-            col("ExtractedIonChromatogram")
-                .list()
-                .len()
-                .name()
-                .suffix(".Count"),
-            col("ExtractedIonChromatogram")
-                .list()
-                .eval(element().struct_().field_by_name(RETENTION_TIME))
-                .list()
-                .min()
-                .alias("RetentionTime.Min"),
-            col("ExtractedIonChromatogram")
-                .list()
-                .eval(element().struct_().field_by_name(RETENTION_TIME))
-                .list()
-                .max()
-                .alias("RetentionTime.Max"),
-            col("ExtractedIonChromatogram")
-                .list()
-                .eval(element().struct_().field_by_name(SIGNAL))
-                .list()
-                .min()
-                .alias("Signal.Min"),
-            col("ExtractedIonChromatogram")
-                .list()
-                .eval(element().struct_().field_by_name(SIGNAL))
-                .list()
-                .max()
-                .alias("Signal.Max"),
-            col("ExtractedIonChromatogram")
-                .list()
-                .eval(element().struct_().field_by_name(SIGNAL))
-                .list()
-                .sum()
-                .alias("Signal.Sum"),
-        ]);
+    if key.smoothing.smoother != Smoother::None {
+        lazy_frame = smooth_ion_chromatograms(lazy_frame, &scans, key)?;
     }
-    lazy_frame = lazy_frame.sort([MASS_TO_CHARGE], Default::default());
-    lazy_frame
+    lazy_frame = lazy_frame.with_columns([
+        col("ExtractedIonChromatogram")
+            .list()
+            .len()
+            .name()
+            .suffix(".Count"),
+        col("ExtractedIonChromatogram")
+            .list()
+            .eval(element().struct_().field_by_name(RETENTION_TIME))
+            .list()
+            .min()
+            .alias("RetentionTime.Min"),
+        col("ExtractedIonChromatogram")
+            .list()
+            .eval(element().struct_().field_by_name(RETENTION_TIME))
+            .list()
+            .max()
+            .alias("RetentionTime.Max"),
+        col("ExtractedIonChromatogram")
+            .list()
+            .eval(element().struct_().field_by_name(SIGNAL))
+            .list()
+            .min()
+            .alias("Signal.Min"),
+        col("ExtractedIonChromatogram")
+            .list()
+            .eval(element().struct_().field_by_name(SIGNAL))
+            .list()
+            .max()
+            .alias("Signal.Max"),
+        col("ExtractedIonChromatogram")
+            .list()
+            .eval(element().struct_().field_by_name(SIGNAL))
+            .list()
+            .sum()
+            .alias("Signal.Sum"),
+    ]);
+    Ok(lazy_frame.sort([MASS_TO_CHARGE], Default::default()))
 }
 
-fn retention_time(mut lazy_frame: LazyFrame, key: Key) -> LazyFrame {
+/// Smooths the signal of each extracted ion chromatogram
+///
+/// A chromatogram misses the scans without a point of its mass to charge, it
+/// is put on the grid of the scans first, the missing signal being zero.
+fn smooth_ion_chromatograms(
+    lazy_frame: LazyFrame,
+    grid: &[i32],
+    key: Key,
+) -> PolarsResult<LazyFrame> {
+    let mut data_frame = lazy_frame.collect()?;
+    let ion_chromatograms = data_frame["ExtractedIonChromatogram"]
+        .list()?
+        .into_iter()
+        .map(|ion_chromatogram| {
+            let Some(ion_chromatogram) = ion_chromatogram else {
+                return Ok(None);
+            };
+            let ion_chromatogram = ion_chromatogram.struct_()?;
+            let retention_time = ion_chromatogram
+                .field_by_name(RETENTION_TIME)?
+                .cast(&DataType::Int32)?;
+            let signal = ion_chromatogram
+                .field_by_name(SIGNAL)?
+                .cast(&DataType::Float64)?;
+            let mut gridded = vec![0.0; grid.len()];
+            for (retention_time, signal) in retention_time.i32()?.iter().zip(signal.f64()?) {
+                if let Some(retention_time) = retention_time
+                    && let Ok(index) = grid.binary_search(&retention_time)
+                {
+                    gridded[index] += signal.unwrap_or_default();
+                }
+            }
+            let retention_time = Series::new(RETENTION_TIME.into(), grid);
+            let signal = Series::new(SIGNAL.into(), smoothing::smooth(&gridded, &key.smoothing));
+            Ok(Some(
+                StructChunked::from_series(
+                    ion_chromatogram.name().clone(),
+                    grid.len(),
+                    [retention_time, signal].iter(),
+                )?
+                .into_series(),
+            ))
+        })
+        .collect::<PolarsResult<ListChunked>>()?
+        .with_name("ExtractedIonChromatogram".into());
+    data_frame.with_column(ion_chromatograms.into_series())?;
+    Ok(data_frame.lazy())
+}
+
+fn retention_time(mut lazy_frame: LazyFrame) -> LazyFrame {
     lazy_frame = lazy_frame
         .sort([MASS_TO_CHARGE], Default::default())
         .group_by([col(RETENTION_TIME)])
@@ -146,54 +248,95 @@ fn retention_time(mut lazy_frame: LazyFrame, key: Key) -> LazyFrame {
             col(SIGNAL).cast(DataType::Float64),
         ])
         .alias(MASS_SPECTRUM)]);
-    if !key.explode {
-        lazy_frame = lazy_frame.with_columns([
-            col(RETENTION_TIME)
-                .cast(DataType::Duration(TimeUnit::Milliseconds))
-                .to_physical()
-                / lit(MINUTES),
-            col(MASS_SPECTRUM)
-                .list()
-                .len()
-                .name()
-                .prefix("_")
-                .name()
-                .suffix(formatcp!(".{COUNT}")),
-            col(MASS_SPECTRUM)
-                .list()
-                .eval(element().struct_().field_by_name(MASS_TO_CHARGE))
-                .list()
-                .min()
-                .alias(formatcp!("_{MASS_TO_CHARGE}.{MIN}")),
-            col(MASS_SPECTRUM)
-                .list()
-                .eval(element().struct_().field_by_name(MASS_TO_CHARGE))
-                .list()
-                .max()
-                .alias(formatcp!("_{MASS_TO_CHARGE}.{MAX}")),
-            col(MASS_SPECTRUM)
-                .list()
-                .eval(element().struct_().field_by_name(SIGNAL))
-                .list()
-                .min()
-                .alias(formatcp!("_{SIGNAL}.{MIN}")),
-            col(MASS_SPECTRUM)
-                .list()
-                .eval(element().struct_().field_by_name(SIGNAL))
-                .list()
-                .max()
-                .alias(formatcp!("_{SIGNAL}.{MAX}")),
-            col(MASS_SPECTRUM)
-                .list()
-                .eval(element().struct_().field_by_name(SIGNAL))
-                .list()
-                .sum()
-                .alias(formatcp!("_{SIGNAL}.{SUM}")),
-        ]);
-    }
+    lazy_frame = lazy_frame.with_columns([
+        col(RETENTION_TIME)
+            .cast(DataType::Duration(TimeUnit::Milliseconds))
+            .to_physical()
+            / lit(MINUTES),
+        col(MASS_SPECTRUM)
+            .list()
+            .len()
+            .name()
+            .prefix("_")
+            .name()
+            .suffix(formatcp!(".{COUNT}")),
+        col(MASS_SPECTRUM)
+            .list()
+            .eval(element().struct_().field_by_name(MASS_TO_CHARGE))
+            .list()
+            .min()
+            .alias(formatcp!("_{MASS_TO_CHARGE}.{MIN}")),
+        col(MASS_SPECTRUM)
+            .list()
+            .eval(element().struct_().field_by_name(MASS_TO_CHARGE))
+            .list()
+            .max()
+            .alias(formatcp!("_{MASS_TO_CHARGE}.{MAX}")),
+        col(MASS_SPECTRUM)
+            .list()
+            .eval(element().struct_().field_by_name(SIGNAL))
+            .list()
+            .min()
+            .alias(formatcp!("_{SIGNAL}.{MIN}")),
+        col(MASS_SPECTRUM)
+            .list()
+            .eval(element().struct_().field_by_name(SIGNAL))
+            .list()
+            .max()
+            .alias(formatcp!("_{SIGNAL}.{MAX}")),
+        col(MASS_SPECTRUM)
+            .list()
+            .eval(element().struct_().field_by_name(SIGNAL))
+            .list()
+            .sum()
+            .alias(formatcp!("_{SIGNAL}.{SUM}")),
+    ]);
     lazy_frame.sort([RETENTION_TIME], Default::default())
 }
 
+/// Smooths the total signal
+fn smooth(lazy_frame: LazyFrame, key: Key) -> PolarsResult<LazyFrame> {
+    if key.sort != Sort::RetentionTime || key.smoothing.smoother == Smoother::None {
+        return Ok(lazy_frame);
+    }
+    let mut data_frame = lazy_frame.collect()?;
+    let signal = data_frame[formatcp!("_{SIGNAL}.{SUM}")].cast(&DataType::Float64)?;
+    let signal = signal
+        .f64()?
+        .iter()
+        .map(Option::unwrap_or_default)
+        .collect::<Vec<_>>();
+    data_frame.with_column(Column::new(
+        PlSmallStr::from_static(formatcp!("_{SIGNAL}.{SUM}")),
+        smoothing::smooth(&signal, &key.smoothing),
+    ))?;
+    Ok(data_frame.lazy())
+}
+
+/// Subtracts the estimated baseline from the total signal, keeping it in the
+/// `_Baseline` column
+fn baseline(lazy_frame: LazyFrame, key: Key) -> PolarsResult<LazyFrame> {
+    if key.sort != Sort::RetentionTime || key.baseline.algorithm == Algorithm::None {
+        return Ok(lazy_frame);
+    }
+    let mut data_frame = lazy_frame.collect()?;
+    let signal = data_frame[formatcp!("_{SIGNAL}.{SUM}")].cast(&DataType::Float64)?;
+    let signal = signal
+        .f64()?
+        .iter()
+        .map(Option::unwrap_or_default)
+        .collect::<Vec<_>>();
+    let baseline = estimate(&signal, &key.baseline);
+    data_frame.with_column(Column::new(
+        PlSmallStr::from_static(formatcp!("_{BASELINE}")),
+        baseline,
+    ))?;
+    Ok(data_frame.lazy().with_column(
+        (col(formatcp!("_{SIGNAL}.{SUM}")).cast(DataType::Float64) - col(formatcp!("_{BASELINE}")))
+            .alias(formatcp!("_{SIGNAL}.{SUM}")),
+    ))
+}
+
 fn rolling(lazy_frame: LazyFrame, key: Key) -> LazyFrame {
     let options = RollingOptionsFixedWindow {
         window_size: key.window_size,
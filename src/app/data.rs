@@ -2,16 +2,33 @@ use crate::{
     r#const::*,
     utils::hash::{HashedDataFrame, HashedMetaDataFrame},
 };
-use anyhow::Result;
+use anyhow::{Result, bail, ensure};
+use bincode::config::{Configuration, Limit, LittleEndian, Varint, standard};
 use metadata::{Metadata, polars::MetaDataFrame};
 use polars::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::{self, Display, Formatter},
     fs::write,
+    io::Cursor,
     path::Path,
+    sync::Arc,
 };
 
+/// Bin container magic
+pub(crate) const MAGIC: &[u8; 4] = b"MSVB";
+/// Bin container version
+const VERSION: u32 = 1;
+/// Parquet file magic
+pub(crate) const PARQUET_MAGIC: &[u8; 4] = b"PAR1";
+/// Arrow IPC file magic
+pub(crate) const IPC_MAGIC: &[u8; 6] = b"ARROW1";
+/// Bin decode limit, a corrupt length prefix fails instead of allocating
+const LIMIT: usize = i32::MAX as _;
+/// Bin decode configuration
+const CONFIGURATION: Configuration<LittleEndian, Varint, Limit<LIMIT>> =
+    standard().with_limit::<LIMIT>();
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub(crate) struct Data {
     pub(crate) frame: HashedMetaDataFrame,
@@ -19,19 +36,135 @@ pub(crate) struct Data {
 
 impl Data {
     pub(crate) fn save(&self, path: impl AsRef<Path>, format: Format) -> Result<()> {
-        let data_frame = self.frame.data.select([RETENTION_TIME, MASS_SPECTRUM])?;
-        match format {
+        write(path, self.bytes(format)?)?;
+        Ok(())
+    }
+
+    /// Serialized metadata and raw data frame, or the spectra only for
+    /// [`Format::Ron`]
+    pub(crate) fn bytes(&self, format: Format) -> Result<Vec<u8>> {
+        let frame = MetaDataFrame::new(&self.frame.meta, &self.frame.data.data_frame);
+        Ok(match format {
             Format::Bin => {
-                // let contents = bincode::serialize(&data_frame)?;
-                // write(path, contents)?;
+                let mut bytes = MAGIC.to_vec();
+                bytes.extend_from_slice(&VERSION.to_le_bytes());
+                bytes.extend(bincode::serde::encode_to_vec(&frame, standard())?);
+                bytes
+            }
+            Format::Ipc => {
+                let mut bytes = Vec::new();
+                let mut writer = IpcWriter::new(&mut bytes);
+                writer.set_custom_schema_metadata(Arc::new(
+                    self.frame
+                        .meta
+                        .iter()
+                        .map(|(key, value)| (key.as_str().into(), value.as_str().into()))
+                        .collect(),
+                ));
+                writer.finish(&mut self.frame.data.data_frame.clone())?;
+                bytes
+            }
+            Format::Parquet => {
+                let mut bytes = Vec::new();
+                ParquetWriter::new(&mut bytes)
+                    .with_key_value_metadata(Some(KeyValueMetadata::from_static(
+                        self.frame
+                            .meta
+                            .iter()
+                            .map(|(key, value)| (key.clone(), value.clone()))
+                            .collect(),
+                    )))
+                    .finish(&mut self.frame.data.data_frame.clone())?;
+                bytes
             }
+            Format::MetaRon => ron::ser::to_string_pretty(&frame, Default::default())?.into_bytes(),
             Format::Ron => {
-                let contents = ron::ser::to_string_pretty(&data_frame, Default::default())?;
-                write(path, contents)?;
+                let data_frame = spectra(&self.frame.data)?;
+                ron::ser::to_string_pretty(&data_frame, Default::default())?.into_bytes()
             }
+        })
+    }
+
+    /// Reads a bin container: `MAGIC`, `VERSION` (u32 LE) and the bincode
+    /// encoded metadata and data frame
+    pub(crate) fn from_bin(bytes: &[u8]) -> Result<Self> {
+        ensure!(bytes.starts_with(MAGIC), "not an MS viewer bin file");
+        let Some(version) = bytes.get(MAGIC.len()..MAGIC.len() + 4) else {
+            bail!("bin file is truncated");
+        };
+        let version = u32::from_le_bytes(version.try_into()?);
+        ensure!(
+            version == VERSION,
+            "bin file version {version} is not supported (expected {VERSION})",
+        );
+        let (frame, _): (MetaDataFrame, _) =
+            bincode::serde::decode_from_slice(&bytes[MAGIC.len() + 4..], CONFIGURATION)?;
+        Ok(Self {
+            frame: MetaDataFrame::new(frame.meta, HashedDataFrame::new(frame.data)?),
+        })
+    }
+
+    /// Reads an Arrow IPC file, the custom schema metadata becomes
+    /// [`Metadata`]
+    pub(crate) fn from_ipc(bytes: &[u8]) -> Result<Self> {
+        let mut reader = IpcReader::new(Cursor::new(bytes));
+        let meta = reader
+            .custom_metadata()?
+            .map(|custom| {
+                metadata(
+                    custom
+                        .iter()
+                        .map(|(key, value)| (key.as_str(), value.as_str())),
+                )
+            })
+            .unwrap_or_default();
+        let data_frame = reader.finish()?;
+        Ok(Self {
+            frame: MetaDataFrame::new(meta, HashedDataFrame::new(data_frame)?),
+        })
+    }
+
+    /// Reads a Parquet file, the file key/value metadata becomes [`Metadata`]
+    pub(crate) fn from_parquet(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ParquetReader::new(Cursor::new(bytes));
+        let meta = match reader.get_metadata()?.key_value_metadata() {
+            Some(key_values) => metadata(key_values.iter().filter_map(|key_value| {
+                Some((key_value.key.as_str(), key_value.value.as_deref()?))
+            })),
+            None => Metadata::default(),
+        };
+        let data_frame = reader.finish()?;
+        Ok(Self {
+            frame: MetaDataFrame::new(meta, HashedDataFrame::new(data_frame)?),
+        })
+    }
+}
+
+/// Spectra frame, `[RETENTION_TIME, MASS_SPECTRUM]`, a raw frame is grouped
+/// by retention time
+fn spectra(data_frame: &DataFrame) -> PolarsResult<DataFrame> {
+    if data_frame.schema().contains(MASS_SPECTRUM) {
+        return data_frame.select([RETENTION_TIME, MASS_SPECTRUM]);
+    }
+    data_frame
+        .clone()
+        .lazy()
+        .sort([MASS_TO_CHARGE], Default::default())
+        .group_by([col(RETENTION_TIME)])
+        .agg([as_struct(vec![col(MASS_TO_CHARGE), col(SIGNAL)]).alias(MASS_SPECTRUM)])
+        .sort([RETENTION_TIME], Default::default())
+        .collect()
+}
+
+/// Metadata from file key/value pairs, skipping the ones written by Arrow
+fn metadata<'a>(key_values: impl IntoIterator<Item = (&'a str, &'a str)>) -> Metadata {
+    let mut meta = Metadata::default();
+    for (key, value) in key_values {
+        if !key.starts_with("ARROW:") {
+            meta.insert(key.to_owned(), value.to_owned());
         }
-        Ok(())
     }
+    meta
 }
 
 impl Display for Data {
@@ -60,9 +193,142 @@ impl Default for Data {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+/// Save format
+///
+/// [`Format::Ron`] keeps the original layout, the spectra frame without
+/// metadata, the loader reads it back as a raw frame. [`Format::MetaRon`] is
+/// the metadata and raw frame container.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub(crate) enum Format {
     #[default]
     Bin,
+    Ipc,
+    MetaRon,
+    Parquet,
     Ron,
 }
+
+impl Format {
+    pub(crate) const ALL: [Self; 5] = [
+        Self::MetaRon,
+        Self::Ron,
+        Self::Bin,
+        Self::Parquet,
+        Self::Ipc,
+    ];
+
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::Bin => "BIN",
+            Self::Ipc => "IPC",
+            Self::MetaRon => "RON",
+            Self::Parquet => "Parquet",
+            Self::Ron => "RON (spectra)",
+        }
+    }
+
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            Self::Bin => "msv.bin",
+            Self::Ipc => "arrow",
+            Self::MetaRon => "msv.ron",
+            Self::Parquet => "parquet",
+            Self::Ron => "ron",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata::NAME;
+
+    fn data() -> Result<Data> {
+        let data_frame = df! {
+            RETENTION_TIME => [1000i32, 1000, 2000],
+            MASS_TO_CHARGE => [51.0f32, 50.0, 50.0],
+            SIGNAL => [20u16, 10, 30],
+        }?;
+        let mut meta = Metadata::default();
+        meta.insert(NAME.to_owned(), "Run".to_owned());
+        Ok(Data {
+            frame: MetaDataFrame::new(meta, HashedDataFrame::new(data_frame)?),
+        })
+    }
+
+    #[test]
+    fn bin() -> Result<()> {
+        let data = data()?;
+        let bytes = data.bytes(Format::Bin)?;
+        let read = Data::from_bin(&bytes)?;
+        assert_eq!(read.frame.meta.get(NAME).map(String::as_str), Some("Run"));
+        assert!(read.frame.data.equals(&data.frame.data));
+        assert_eq!(read.frame.data.hash, data.frame.data.hash);
+        Ok(())
+    }
+
+    #[test]
+    fn bin_errors() -> Result<()> {
+        let bytes = data()?.bytes(Format::Bin)?;
+        assert!(Data::from_bin(b"MSVX\x01\0\0\0").is_err());
+        assert!(Data::from_bin(&bytes[..MAGIC.len() + 2]).is_err());
+        let mut version = bytes.clone();
+        version[MAGIC.len()] = 2;
+        assert!(Data::from_bin(&version).is_err());
+        assert!(Data::from_bin(&bytes[..bytes.len() - 1]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn spectra() -> Result<()> {
+        let data_frame = super::spectra(&data()?.frame.data)?;
+        assert_eq!(
+            data_frame.get_column_names_str(),
+            [RETENTION_TIME, MASS_SPECTRUM]
+        );
+        assert_eq!(data_frame.height(), 2);
+        let mass_spectrum = data_frame[MASS_SPECTRUM].list()?;
+        let first = mass_spectrum.get_as_series(0).unwrap();
+        let mass_to_charge = first.struct_()?.field_by_name(MASS_TO_CHARGE)?;
+        assert_eq!(
+            mass_to_charge
+                .f32()?
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            [50.0, 51.0],
+        );
+        // Grouped frames are kept as they are
+        assert!(super::spectra(&data_frame)?.equals(&data_frame));
+        Ok(())
+    }
+
+    #[test]
+    fn ron() -> Result<()> {
+        let bytes = data()?.bytes(Format::Ron)?;
+        let frame = crate::app::readers::read("run.ron", &bytes)?;
+        assert_eq!(frame.meta.get(NAME).map(String::as_str), Some("run"));
+        let data_frame = &frame.data.data_frame;
+        assert_eq!(
+            data_frame[RETENTION_TIME]
+                .i32()?
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            [1000, 1000, 2000],
+        );
+        assert_eq!(
+            data_frame[MASS_TO_CHARGE]
+                .f32()?
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            [50.0, 51.0, 50.0],
+        );
+        assert_eq!(
+            data_frame[SIGNAL]
+                .u16()?
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            [10, 20, 30],
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use super::notifications;
+use anyhow::Result;
+use egui::Context;
+use std::sync::{Arc, Mutex};
+
+/// Files picked by the open dialog, name and bytes
+///
+/// Shared because the web picker delivers the files asynchronously.
+pub(crate) type Files = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+
+/// Shows the platform file picker, the picked files are pushed to `files`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn open(_ctx: &Context, files: &Files) {
+    let Some(paths) = rfd::FileDialog::new().pick_files() else {
+        return;
+    };
+    for path in paths {
+        let name = path.display().to_string();
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                if let Ok(mut files) = files.lock() {
+                    files.push((name, bytes));
+                }
+            }
+            Err(error) => notifications::error(name, error),
+        }
+    }
+}
+
+/// Shows the browser file picker (`<input type=file>`), the picked files are
+/// pushed to `files` once read
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn open(ctx: &Context, files: &Files) {
+    use wasm_bindgen::{JsCast, closure::Closure};
+    use wasm_bindgen_futures::{JsFuture, spawn_local};
+    use web_sys::HtmlInputElement;
+
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        return;
+    };
+    let Ok(input) = document.create_element("input") else {
+        return;
+    };
+    let input = input.unchecked_into::<HtmlInputElement>();
+    input.set_type("file");
+    input.set_multiple(true);
+    let target = input.clone();
+    let ctx = ctx.clone();
+    let files = files.clone();
+    let onchange = Closure::once_into_js(move || {
+        let Some(list) = target.files() else {
+            return;
+        };
+        for index in 0..list.length() {
+            let Some(file) = list.get(index) else {
+                continue;
+            };
+            let ctx = ctx.clone();
+            let files = files.clone();
+            spawn_local(async move {
+                let name = file.name();
+                match JsFuture::from(file.array_buffer()).await {
+                    Ok(buffer) => {
+                        let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+                        if let Ok(mut files) = files.lock() {
+                            files.push((name, bytes));
+                        }
+                        ctx.request_repaint();
+                    }
+                    Err(error) => notifications::error(name, format!("{error:?}")),
+                }
+            });
+        }
+    });
+    input.set_onchange(Some(onchange.unchecked_ref()));
+    input.click();
+}
+
+/// Saves the files, name and bytes
+///
+/// A single file is saved to the path chosen in the platform save dialog, a
+/// few files to the chosen folder. Cancelling the dialog saves nothing.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn save(files: Vec<(String, Vec<u8>)>) -> Result<()> {
+    if let [(name, bytes)] = &files[..] {
+        if let Some(path) = rfd::FileDialog::new().set_file_name(name).save_file() {
+            std::fs::write(path, bytes)?;
+        }
+    } else if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+        for (name, bytes) in files {
+            std::fs::write(folder.join(name), bytes)?;
+        }
+    }
+    Ok(())
+}
+
+/// Saves the files, name and bytes, as a browser download
+///
+/// A few files are bundled into a single zip archive, browsers block or ask
+/// about several downloads started at once.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn save(files: Vec<(String, Vec<u8>)>) -> Result<()> {
+    use anyhow::anyhow;
+    use std::io::{Cursor, Write};
+    use wasm_bindgen::{JsCast, closure::Closure};
+    use web_sys::{Blob, HtmlAnchorElement, Url};
+    use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+    /// Delay before the object URL is revoked, the download starts
+    /// asynchronously after the click
+    const REVOKE_TIMEOUT: i32 = 60_000;
+
+    let error = |error: wasm_bindgen::JsValue| anyhow!("{error:?}");
+    let (name, bytes) = match <[_; 1]>::try_from(files) {
+        Ok([file]) => file,
+        Err(files) => {
+            let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+            let options =
+                SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+            for (name, bytes) in files {
+                writer.start_file(name, options)?;
+                writer.write_all(&bytes)?;
+            }
+            ("runs.zip".to_owned(), writer.finish()?.into_inner())
+        }
+    };
+    let window = web_sys::window().ok_or_else(|| anyhow!("no window"))?;
+    let document = window.document().ok_or_else(|| anyhow!("no document"))?;
+    let array = js_sys::Array::of1(&js_sys::Uint8Array::from(&bytes[..]));
+    let blob = Blob::new_with_u8_array_sequence(&array).map_err(error)?;
+    let url = Url::create_object_url_with_blob(&blob).map_err(error)?;
+    let anchor = document
+        .create_element("a")
+        .map_err(error)?
+        .unchecked_into::<HtmlAnchorElement>();
+    anchor.set_href(&url);
+    anchor.set_download(&name);
+    anchor.click();
+    let revoke = Closure::once_into_js(move || {
+        let _ = Url::revoke_object_url(&url);
+    });
+    window
+        .set_timeout_with_callback_and_timeout_and_arguments_0(
+            revoke.unchecked_ref(),
+            REVOKE_TIMEOUT,
+        )
+        .map_err(error)?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use egui::{Context, Id};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+const ID: &str = "Library";
+
+/// Spectral library
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Library {
+    pub(crate) entries: Vec<Entry>,
+    pub(crate) hash: u64,
+}
+
+impl Library {
+    pub(crate) fn new(entries: Vec<Entry>) -> Self {
+        let mut hasher = DefaultHasher::new();
+        entries.hash(&mut hasher);
+        Self {
+            entries,
+            hash: hasher.finish(),
+        }
+    }
+
+    /// Library loaded into the context
+    pub(crate) fn get(ctx: &Context) -> Option<Arc<Self>> {
+        ctx.data(|data| data.get_temp(Id::new(ID)))
+    }
+
+    /// Appends entries to the library loaded into the context
+    pub(crate) fn extend(ctx: &Context, entries: Vec<Entry>) {
+        ctx.data_mut(|data| {
+            let library = data.get_temp_mut_or_default::<Arc<Self>>(Id::new(ID));
+            let mut extended = library.entries.clone();
+            extended.extend(entries);
+            *library = Arc::new(Self::new(extended));
+        });
+    }
+
+    pub(crate) fn clear(ctx: &Context) {
+        ctx.data_mut(|data| data.remove_temp::<Arc<Self>>(Id::new(ID)));
+    }
+}
+
+impl Eq for Library {}
+
+impl PartialEq for Library {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+    }
+}
+
+impl Hash for Library {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+/// Library entry
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Entry {
+    pub(crate) name: String,
+    pub(crate) formula: Option<String>,
+    pub(crate) molecular_weight: Option<f64>,
+    pub(crate) cas: Option<String>,
+    pub(crate) comment: Option<String>,
+    /// Mass to charge and signal pairs
+    pub(crate) peaks: Vec<(f32, f32)>,
+}
+
+impl Hash for Entry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.formula.hash(state);
+        self.molecular_weight.map(f64::to_bits).hash(state);
+        self.cas.hash(state);
+        self.comment.hash(state);
+        for (mass_to_charge, signal) in &self.peaks {
+            mass_to_charge.to_bits().hash(state);
+            signal.to_bits().hash(state);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{app::states::settings::Settings, utils::hash::HashedMetaDataFrame};
+use egui::Id;
+use egui_plot::Plot;
+
+const ID: &str = "Link";
+
+/// Link of the pane
+///
+/// Panes of the same link group showing the same run share the retention time
+/// axis, the hover cursor and the selections.
+pub(crate) fn link(frame: &HashedMetaDataFrame, settings: &Settings) -> Option<Id> {
+    Some(Id::new(ID).with(settings.link?).with(frame.data.hash))
+}
+
+/// Scope of the selections of the pane
+///
+/// The link of a linked pane, otherwise the run, the unlinked panes of a run
+/// share the selections.
+pub(crate) fn scope(frame: &HashedMetaDataFrame, settings: &Settings) -> Id {
+    link(frame, settings).unwrap_or_else(|| Id::new(ID).with(frame.data.hash))
+}
+
+/// Links the retention time axis and cursor of the plot
+pub(crate) fn plot(plot: Plot<'_>, link: Option<Id>) -> Plot<'_> {
+    match link {
+        Some(link) => plot
+            .link_axis(link, [true, false])
+            .link_cursor(link, [true, false]),
+        None => plot,
+    }
+}
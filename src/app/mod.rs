@@ -1,10 +1,6 @@
 use self::panes::{Pane, behavior::Behavior};
-use crate::utils::{
-    TreeExt,
-    hash::{HashedDataFrame, HashedMetaDataFrame},
-};
+use crate::utils::{TreeExt, hash::HashedMetaDataFrame};
 use anyhow::Result;
-use data::{Data, Format};
 use eframe::{APP_KEY, CreationContext, Storage, get_value, set_value};
 use egui::{
     Align, Align2, CentralPanel, Color32, Context, DroppedFile, FontDefinitions, Frame, Id,
@@ -15,17 +11,24 @@ use egui_ext::{DroppedFileExt, HoveredFileExt, LightDarkButton};
 use egui_phosphor::{
     Variant, add_to_fonts,
     regular::{
-        ARROWS_CLOCKWISE, FLOPPY_DISK, GRID_FOUR, ROCKET, SIDEBAR_SIMPLE, SQUARE_SPLIT_HORIZONTAL,
-        SQUARE_SPLIT_VERTICAL, TABLE, TABS, TRASH,
+        ALIGN_CENTER_HORIZONTAL, ARROWS_CLOCKWISE, FLOPPY_DISK, FOLDER_OPEN, GRID_FOUR,
+        MINUS_SQUARE, ROCKET, SIDEBAR_SIMPLE, SQUARE_SPLIT_HORIZONTAL, SQUARE_SPLIT_VERTICAL,
+        TABLE, TABS, TRASH,
     },
 };
 use egui_tiles::{ContainerKind, Tile, Tree};
-use metadata::polars::MetaDataFrame;
+use file_dialog::Files;
+use library::Library;
 use panes::table::TablePane;
 use polars::frame::DataFrame;
+use readers::Kind;
 use serde::{Deserialize, Serialize};
 use std::{fmt::Write, io::Cursor, str, time::Duration};
-use tracing::{error, info, trace};
+use tracing::{info, trace};
+use windows::{
+    alignment::AlignmentWindow, import::ImportWindow, save::SaveWindow,
+    subtraction::SubtractionWindow,
+};
 
 macro icon($icon:expr) {
     RichText::new($icon).size(SIZE)
@@ -37,7 +40,7 @@ macro localize($text:literal) {
 
 /// IEEE 754-2008
 const MAX_PRECISION: usize = 16;
-const _NOTIFICATIONS_DURATION: Duration = Duration::from_secs(15);
+const NOTIFICATIONS_DURATION: Duration = Duration::from_secs(15);
 const SIZE: f32 = 32.0;
 
 #[derive(Deserialize, Serialize)]
@@ -49,6 +52,18 @@ pub struct App {
     // Panes
     tree: Tree<Pane>,
     behavior: Behavior,
+    // Windows
+    #[serde(skip)]
+    imports: Vec<ImportWindow>,
+    #[serde(skip)]
+    subtraction: Option<SubtractionWindow>,
+    #[serde(skip)]
+    alignment: Option<AlignmentWindow>,
+    #[serde(skip)]
+    save: Option<SaveWindow>,
+    // Files picked by the open dialog
+    #[serde(skip)]
+    files: Files,
 }
 
 impl Default for App {
@@ -58,6 +73,11 @@ impl Default for App {
             left_panel: true,
             tree: Tree::empty("tree"),
             behavior: Default::default(),
+            imports: Vec::new(),
+            subtraction: None,
+            alignment: None,
+            save: None,
+            files: Default::default(),
         }
     }
 }
@@ -79,6 +99,61 @@ impl App {
             .unwrap_or_default()
     }
 
+    /// Loads the files passed on the command line, inserts them as plot panes
+    /// instead of table panes when `plot` is set
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(mut self, ctx: &Context, paths: &[std::path::PathBuf], plot: bool) -> Self {
+        for path in paths {
+            let name = path.display().to_string();
+            let result = std::fs::read(path)
+                .map_err(Into::into)
+                .and_then(|bytes| self.load(ctx, &name, &bytes, plot));
+            if let Err(error) = result {
+                notifications::error(name, error);
+            }
+        }
+        self
+    }
+
+    /// Loads a file and inserts it as a table (or plot) pane, delimited text
+    /// opens an import window first, spectral libraries extend the loaded
+    /// library
+    fn load(&mut self, ctx: &Context, name: &str, bytes: &[u8], plot: bool) -> Result<()> {
+        match Kind::new(name, bytes) {
+            Kind::Delimited => {
+                self.imports
+                    .push(ImportWindow::new(name.to_owned(), bytes.to_vec()));
+                return Ok(());
+            }
+            Kind::Msp => {
+                Library::extend(ctx, readers::msp::read(bytes)?);
+                return Ok(());
+            }
+            _ => {}
+        }
+        let frame = readers::read(name, bytes)?;
+        trace!(?frame);
+        let pane = Pane::Table(TablePane {
+            frame,
+            settings: Default::default(),
+        });
+        self.tree.insert_pane(if plot { pane.plot() } else { pane });
+        Ok(())
+    }
+
+    /// Loads the files picked by the open dialog
+    fn file_dialog(&mut self, ctx: &Context) {
+        let files = match self.files.lock() {
+            Ok(mut files) => std::mem::take(&mut *files),
+            Err(_) => return,
+        };
+        for (name, bytes) in files {
+            if let Err(error) = self.load(ctx, &name, &bytes, false) {
+                notifications::error(name, error);
+            }
+        }
+    }
+
     fn drag_and_drop(&mut self, ctx: &Context) {
         // Preview hovering files
         if let Some(text) = ctx.input(|input| {
@@ -108,36 +183,59 @@ impl App {
         }) {
             info!(?dropped_files);
             for dropped_file in dropped_files {
-                // let data_frame: DataFrame = match dropped_file.extension() {
-                //     Some("bin") => bincode::deserialize(&fs::read(&args.path)?)?,
-                //     Some("ron") => ron::de::from_str(&fs::read_to_string(&args.path)?)?,
-                //     _ => panic!("unsupported input file extension"),
-                // };
-                // dropped_file.extension();
-                let bytes = dropped_file.bytes().unwrap();
-                let frame: MetaDataFrame = ron::de::from_bytes(&bytes).unwrap();
-                let data = HashedDataFrame::new(frame.data).unwrap();
+                let name = dropped_file.display().to_string();
+                let bytes = match dropped_file.bytes() {
+                    Ok(bytes) => bytes,
+                    Err(error) => {
+                        notifications::error(name, error);
+                        continue;
+                    }
+                };
+                if let Err(error) = self.load(ctx, &name, &bytes, false) {
+                    notifications::error(name, error);
+                }
+            }
+        }
+    }
+}
+
+impl App {
+    fn windows(&mut self, ctx: &Context) {
+        for import in &mut self.imports {
+            if let Some(frame) = import.show(ctx) {
                 self.tree.insert_pane(Pane::Table(TablePane {
-                    frame: MetaDataFrame::new(frame.meta, data),
+                    frame,
+                    settings: Default::default(),
+                }));
+            }
+        }
+        self.imports.retain(|import| import.open);
+        if let Some(subtraction) = &mut self.subtraction {
+            if let Some(frame) = subtraction.show(ctx) {
+                self.tree.insert_pane(Pane::Table(TablePane {
+                    frame,
                     settings: Default::default(),
                 }));
-                // match bin(&dropped_file) {
-                //     Ok(data_frame) => {
-                //         trace!(?data_frame);
-                //         self.tree.insert_pane(Pane::Table(TablePane {
-                //             data_frame,
-                //             settings: Default::default(),
-                //         }));
-                //     }
-                //     Err(error) => {
-                //         error!(%error);
-                //         // self.toasts
-                //         //     .error(format!("{}: {error}", dropped.display()))
-                //         //     .set_closable(true)
-                //         //     .set_duration(Some(NOTIFICATIONS_DURATION));
-                //         continue;
-                //     }
-                // };
+            }
+            if !subtraction.open {
+                self.subtraction = None;
+            }
+        }
+        if let Some(alignment) = &mut self.alignment {
+            for frame in alignment.show(ctx) {
+                self.tree.insert_pane(Pane::Table(TablePane {
+                    frame,
+                    settings: Default::default(),
+                }));
+            }
+            if !alignment.open {
+                self.alignment = None;
+            }
+        }
+        if let Some(save) = &mut self.save {
+            save.show(ctx);
+            if !save.open {
+                self.save = None;
             }
         }
     }
@@ -165,6 +263,7 @@ impl App {
     // Central panel
     fn central_panel(&mut self, ctx: &Context) {
         CentralPanel::default().show(ctx, |ui| {
+            self.behavior.frames = panes::frames(&self.tree);
             self.tree.ui(&mut self.behavior, ui);
             if let Some(id) = self.behavior.close.take() {
                 self.tree.tiles.remove(id);
@@ -200,6 +299,14 @@ impl App {
                     .on_hover_text(localize!("reactive_description_enabled"))
                     .on_disabled_hover_text(localize!("reactive_description_disabled"));
                 ui.separator();
+                if ui
+                    .button(icon!(FOLDER_OPEN))
+                    .on_hover_text(localize!("open"))
+                    .clicked()
+                {
+                    file_dialog::open(ui.ctx(), &self.files);
+                }
+                ui.separator();
                 if ui
                     .button(icon!(TRASH))
                     .on_hover_text(localize!("reset_application"))
@@ -263,40 +370,41 @@ impl App {
                 //
                 ui.separator();
                 // Save
-                ui.menu_button(icon!(FLOPPY_DISK), |ui| {
-                    if ui.button("RON").clicked() {
-                        for tile_id in self.tree.active_tiles() {
-                            if let Some(tile) = self.tree.tiles.get(tile_id) {
-                                match tile {
-                                    Tile::Pane(pane) => {
-                                        Data {
-                                            frame: pane.frame().clone(),
-                                        }
-                                        .save("df.msv.ron", Format::Ron)
-                                        .unwrap();
-                                    }
-                                    Tile::Container(container) => {}
-                                }
-                            }
-                        }
-                    }
-                    if ui.button("BIN").clicked() {
-                        for tile_id in self.tree.active_tiles() {
-                            if let Some(tile) = self.tree.tiles.get(tile_id) {
-                                match tile {
-                                    Tile::Pane(pane) => {
-                                        Data {
-                                            frame: pane.frame().clone(),
-                                        }
-                                        .save("df.msv.bin", Format::Ron)
-                                        .unwrap();
-                                    }
-                                    Tile::Container(container) => {}
-                                }
+                if ui
+                    .button(icon!(FLOPPY_DISK))
+                    .on_hover_text(localize!("save"))
+                    .clicked()
+                {
+                    let panes = self
+                        .tree
+                        .active_tiles()
+                        .into_iter()
+                        .filter_map(|tile_id| match self.tree.tiles.get(tile_id) {
+                            Some(Tile::Pane(pane)) => {
+                                let (frame, settings) = pane.parts();
+                                Some((frame.clone(), settings.clone()))
                             }
-                        }
-                    }
-                });
+                            _ => None,
+                        })
+                        .collect();
+                    self.save = Some(SaveWindow::new(panes));
+                }
+                // Blank subtraction
+                if ui
+                    .button(icon!(MINUS_SQUARE))
+                    .on_hover_text(localize!("blank_subtraction"))
+                    .clicked()
+                {
+                    self.subtraction = Some(SubtractionWindow::new(panes::frames(&self.tree)));
+                }
+                // Retention time alignment
+                if ui
+                    .button(icon!(ALIGN_CENTER_HORIZONTAL))
+                    .on_hover_text(localize!("alignment"))
+                    .clicked()
+                {
+                    self.alignment = Some(AlignmentWindow::new(panes::frames(&self.tree)));
+                }
                 {
                     // for tile_id in self.tree.active_tiles() {
                     //     if let Some(root) = self.tree.root() {
@@ -374,7 +482,10 @@ impl eframe::App for App {
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         self.panels(ctx);
+        self.windows(ctx);
+        self.file_dialog(ctx);
         self.drag_and_drop(ctx);
+        notifications::show(ctx);
         if self.reactive {
             ctx.request_repaint();
         }
@@ -385,8 +496,19 @@ impl eframe::App for App {
 //     Ok(bincode::deserialize(&dropped_file.bytes()?)?)
 // }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+
+mod calibration;
 mod computers;
 mod data;
+mod file_dialog;
+mod library;
+mod link;
+mod notifications;
 mod panes;
+mod readers;
+mod selection;
 mod states;
 mod widgets;
+mod windows;
@@ -0,0 +1,65 @@
+use super::NOTIFICATIONS_DURATION;
+use egui::{Align2, Area, Context, Frame, Id, Order, RichText, Ui};
+use egui_phosphor::regular::{WARNING, X};
+use std::{fmt::Display, sync::Mutex};
+use tracing::error;
+
+/// Errors reported since the last frame
+///
+/// A queue instead of the context data, the computers report errors without
+/// access to the context.
+static QUEUE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Reports an error, shown as a toast notification
+pub(crate) fn error(context: impl Display, error: impl Display) {
+    let text = format!("{context}: {error:#}");
+    error!("{text}");
+    if let Ok(mut queue) = QUEUE.lock() {
+        queue.push(text);
+    }
+}
+
+/// Shows the notifications in the bottom right corner until they expire or
+/// are closed
+pub(crate) fn show(ctx: &Context) {
+    let id = Id::new("Notifications");
+    let time = ctx.input(|input| input.time);
+    let mut notifications =
+        ctx.data_mut(|data| data.get_temp::<Vec<(String, f64)>>(id).unwrap_or_default());
+    if let Ok(mut queue) = QUEUE.lock() {
+        for text in queue.drain(..) {
+            // Repeated errors restart the timer instead of stacking
+            notifications.retain(|(shown, _)| *shown != text);
+            notifications.push((text, time + NOTIFICATIONS_DURATION.as_secs_f64()));
+        }
+    }
+    notifications.retain(|&(_, expires)| expires > time);
+    if let Some(expires) = notifications
+        .iter()
+        .map(|&(_, expires)| expires)
+        .min_by(f64::total_cmp)
+    {
+        ctx.request_repaint_after_secs((expires - time) as _);
+    }
+    Area::new(id)
+        .order(Order::Foreground)
+        .anchor(Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+        .show(ctx, |ui| {
+            notifications.retain(|(text, _)| !notification(ui, text));
+        });
+    ctx.data_mut(|data| data.insert_temp(id, notifications));
+}
+
+/// Shows a notification, returns whether it was closed
+fn notification(ui: &mut Ui, text: &str) -> bool {
+    let mut closed = false;
+    Frame::popup(ui.style()).show(ui, |ui| {
+        ui.set_max_width(320.0);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(WARNING).color(ui.visuals().error_fg_color));
+            ui.label(text);
+            closed = ui.small_button(X).on_hover_text("Close").clicked();
+        });
+    });
+    closed
+}
@@ -1,10 +1,12 @@
-use super::{Pane, plot::PlotPane, table::TablePane};
+use super::{Pane, frames};
 use crate::{
     app::{icon, localize},
-    utils::ContainerExt,
+    utils::{ContainerExt, hash::HashedMetaDataFrame},
+};
+use egui::{CollapsingHeader, CursorIcon, DragValue, RichText, Ui, WidgetText};
+use egui_phosphor::regular::{
+    BARCODE, CHART_BAR, CHART_LINE, GRID_FOUR, LINK, MAGNIFYING_GLASS, STACK, TABLE, X,
 };
-use egui::{CollapsingHeader, CursorIcon, RichText, Ui, WidgetText};
-use egui_phosphor::regular::{CHART_BAR, LINK, TABLE, X};
 use egui_tiles::{Tile, TileId, Tiles, Tree, UiResponse};
 use serde::{Deserialize, Serialize};
 
@@ -15,11 +17,16 @@ const SIZE: f32 = 16.0;
 pub(crate) struct Behavior {
     pub(crate) close: Option<TileId>,
     pub(crate) click: Option<TileId>,
+    /// Loaded runs, the comparison panes resolve their runs against them
+    #[serde(skip)]
+    pub(crate) frames: Vec<HashedMetaDataFrame>,
 }
 
 impl Behavior {
     pub(crate) fn settings(&mut self, ui: &mut Ui, tree: &mut Tree<Pane>) {
         ui.separator();
+        // Loaded runs, for the comparison panes
+        let frames = frames(tree);
         for tile_id in tree.active_tiles() {
             if let Some(Tile::Pane(pane)) = tree.tiles.get_mut(tile_id) {
                 ui.visuals_mut().collapsing_header_frame = true;
@@ -37,34 +44,83 @@ impl Behavior {
                 CollapsingHeader::new(RichText::new(pane.title()).heading())
                     .open(open)
                     .show(ui, |ui| {
-                        let text = match pane {
-                            Pane::Plot(_) => TABLE,
-                            Pane::Table(_) => CHART_BAR,
-                        };
-                        if ui
-                            .button(icon!(text).size(16.0))
-                            .on_hover_text(localize!("table"))
-                            .clicked()
-                        {
-                            *pane = match pane {
-                                Pane::Plot(PlotPane { frame, settings }) => {
-                                    Pane::Table(TablePane {
-                                        frame: frame.clone(),
-                                        settings: *settings,
-                                    })
-                                }
-                                Pane::Table(TablePane { frame, settings }) => {
-                                    Pane::Plot(PlotPane {
-                                        frame: frame.clone(),
-                                        settings: *settings,
-                                    })
-                                }
-                            };
-                            // if let Some(id) = self.tree.iter {
-                            //     // if let Some(Tile::Container(container)) = self.tree.tiles.get_mut(id) {
-                            //     //     container.set_kind(ContainerKind::Tabs);
-                            //     // }
-                            // }
+                        ui.horizontal(|ui| {
+                            if !matches!(pane, Pane::Table(_))
+                                && ui
+                                    .button(icon!(TABLE).size(SIZE))
+                                    .on_hover_text(localize!("table"))
+                                    .clicked()
+                            {
+                                *pane = pane.table();
+                            }
+                            if !matches!(pane, Pane::Plot(_))
+                                && ui
+                                    .button(icon!(CHART_BAR).size(SIZE))
+                                    .on_hover_text(localize!("plot"))
+                                    .clicked()
+                            {
+                                *pane = pane.plot();
+                            }
+                            if !matches!(pane, Pane::Peaks(_))
+                                && ui
+                                    .button(icon!(CHART_LINE).size(SIZE))
+                                    .on_hover_text(localize!("peaks"))
+                                    .clicked()
+                            {
+                                *pane = pane.peaks();
+                            }
+                            if !matches!(pane, Pane::Search(_))
+                                && ui
+                                    .button(icon!(MAGNIFYING_GLASS).size(SIZE))
+                                    .on_hover_text(localize!("search"))
+                                    .clicked()
+                            {
+                                *pane = pane.search();
+                            }
+                            if !matches!(pane, Pane::Comparison(_))
+                                && ui
+                                    .button(icon!(STACK).size(SIZE))
+                                    .on_hover_text(localize!("comparison"))
+                                    .clicked()
+                            {
+                                *pane = pane.comparison();
+                            }
+                            if !matches!(pane, Pane::HeatMap(_))
+                                && ui
+                                    .button(icon!(GRID_FOUR).size(SIZE))
+                                    .on_hover_text(localize!("heat_map"))
+                                    .clicked()
+                            {
+                                *pane = pane.heat_map();
+                            }
+                            if !matches!(pane, Pane::Spectrum(_))
+                                && ui
+                                    .button(icon!(BARCODE).size(SIZE))
+                                    .on_hover_text(localize!("spectrum"))
+                                    .clicked()
+                            {
+                                *pane = pane.spectrum();
+                            }
+                            ui.separator();
+                            // Link group
+                            let link = pane.link_mut();
+                            if ui
+                                .selectable_label(link.is_some(), icon!(LINK).size(SIZE))
+                                .on_hover_text(localize!("link"))
+                                .clicked()
+                            {
+                                *link = if link.is_some() { None } else { Some(0) };
+                            }
+                            if let Some(group) = link {
+                                ui.add(DragValue::new(group)).on_hover_text(
+                                    "Link group, the panes of a group showing the same run share \
+                                     the retention time axis, the cursor and the selections",
+                                );
+                            }
+                        });
+                        if let Pane::Comparison(comparison) = pane {
+                            comparison.frames(ui, &frames);
+                            ui.separator();
                         }
                         pane.settings(ui);
                     });
@@ -110,7 +166,7 @@ impl egui_tiles::Behavior<Pane> for Behavior {
         if response.clicked() {
             self.click = Some(tile_id);
         }
-        pane.ui(ui);
+        pane.ui(ui, &self.frames);
         if response.dragged() {
             UiResponse::DragStarted
         } else {
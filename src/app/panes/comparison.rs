@@ -0,0 +1,144 @@
+use crate::{
+    app::{
+        computers::{
+            comparison::{Computed as ComparisonComputed, Key as ComparisonKey},
+            table::{Computed as TableComputed, Key as TableKey},
+        },
+        link::{self, link, scope},
+        selection::Selection,
+        states::settings::{Normalization, Settings, Sort},
+    },
+    utils::hash::HashedMetaDataFrame,
+};
+use egui::Ui;
+use egui_ext::color;
+use egui_plot::{Legend, Line, Plot};
+use metadata::NAME;
+use serde::{Deserialize, Serialize};
+use std::iter::once;
+
+/// Comparison pane, overlays the chromatograms of several runs
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct ComparisonPane {
+    /// Reference run
+    pub(crate) frame: HashedMetaDataFrame,
+    /// Data hashes of the runs compared with the reference, resolved against
+    /// the loaded runs
+    pub(crate) runs: Vec<u64>,
+    pub(crate) settings: Settings,
+}
+
+impl ComparisonPane {
+    /// Reference and compared runs, the closed ones are skipped
+    fn runs<'a>(
+        &'a self,
+        frames: &'a [HashedMetaDataFrame],
+    ) -> impl Iterator<Item = &'a HashedMetaDataFrame> {
+        let runs = self
+            .runs
+            .iter()
+            .filter_map(|hash| frames.iter().find(|frame| frame.data.hash == *hash));
+        once(&self.frame).chain(runs)
+    }
+
+    pub(super) fn ui(&self, ui: &mut Ui, frames: &[HashedMetaDataFrame]) {
+        // Chromatograms come from the frames grouped by retention time
+        let settings = Settings {
+            explode: false,
+            sort: Sort::RetentionTime,
+            ..self.settings.clone()
+        };
+        let traces = self
+            .runs(frames)
+            .filter_map(|frame| {
+                let data_frame = ui.memory_mut(|memory| {
+                    memory
+                        .caches
+                        .cache::<TableComputed>()
+                        .get(TableKey::new(&frame.data, &settings))
+                });
+                // Failed computation is notified
+                if data_frame.width() == 0 {
+                    return None;
+                }
+                let mut points = ui.memory_mut(|memory| {
+                    memory
+                        .caches
+                        .cache::<ComparisonComputed>()
+                        .get(ComparisonKey::new(&data_frame, &settings))
+                        .points
+                });
+                normalize(&mut points, settings.comparison.normalization);
+                Some((name(frame), points))
+            })
+            .collect::<Vec<_>>();
+        // Offset is a fraction of the highest trace
+        let max = traces
+            .iter()
+            .flat_map(|(_, points)| points.iter().map(|[_, signal]| *signal))
+            .fold(0.0, f64::max);
+        let offset = settings.comparison.offset * max;
+        let mut plot = Plot::new("Comparison");
+        if settings.plot.legend {
+            plot = plot.legend(Legend::default());
+        }
+        let link = link(&self.frame, &self.settings);
+        let scope = scope(&self.frame, &self.settings);
+        let selection = Selection::get(ui.ctx(), scope);
+        let shift = ui.input(|input| input.modifiers.shift);
+        let response = link::plot(plot, link).allow_drag(!shift).show(ui, |ui| {
+            for (index, (name, mut points)) in traces.into_iter().enumerate() {
+                for [_, signal] in &mut points {
+                    *signal += index as f64 * offset;
+                }
+                ui.line(Line::new(name, points).color(color(index)));
+            }
+            if let Some(selection) = selection {
+                selection.show(ui);
+            }
+            ui.pointer_coordinate()
+        });
+        Selection::select(ui, scope, &response.response, response.inner);
+    }
+
+    /// Selection of the compared runs among the loaded ones
+    pub(crate) fn frames(&mut self, ui: &mut Ui, frames: &[HashedMetaDataFrame]) {
+        ui.label("Runs");
+        for frame in frames {
+            if frame.data == self.frame.data {
+                continue;
+            }
+            let position = self.runs.iter().position(|&hash| hash == frame.data.hash);
+            let mut checked = position.is_some();
+            if ui.checkbox(&mut checked, name(frame)).changed() {
+                match position {
+                    Some(position) => {
+                        self.runs.remove(position);
+                    }
+                    None => self.runs.push(frame.data.hash),
+                }
+            }
+        }
+    }
+}
+
+fn name(frame: &HashedMetaDataFrame) -> String {
+    frame.meta.get(NAME).cloned().unwrap_or_default()
+}
+
+fn normalize(points: &mut [[f64; 2]], normalization: Normalization) {
+    let divisor = match normalization {
+        Normalization::None => return,
+        Normalization::Maximum => points.iter().map(|[_, signal]| *signal).fold(0.0, f64::max),
+        // Trapezoid area
+        Normalization::Area => points
+            .windows(2)
+            .map(|window| (window[1][0] - window[0][0]) * (window[0][1] + window[1][1]) / 2.0)
+            .sum(),
+    };
+    if divisor > 0.0 {
+        for [_, signal] in points {
+            *signal /= divisor;
+        }
+    }
+}
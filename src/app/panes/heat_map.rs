@@ -0,0 +1,144 @@
+use crate::{
+    app::{
+        computers::{
+            crosshair::{Computed as CrosshairComputed, Key as CrosshairKey},
+            heat_map::{Computed as HeatMapComputed, Key as HeatMapKey},
+            points::{Computed as PointsComputed, Key as PointsKey},
+        },
+        link::{self, link, scope},
+        selection::{self, Selection},
+        states::settings::Settings,
+    },
+    utils::hash::HashedMetaDataFrame,
+};
+use egui::{TextureHandle, TextureOptions, Ui};
+use egui_plot::{Bar, BarChart, HLine, Line, Plot, PlotImage, PlotMemory, PlotPoint, VLine};
+use serde::{Deserialize, Serialize};
+
+/// Height of the spectrum and chromatogram plots
+const HEIGHT: f32 = 160.0;
+/// Minimal mass to charge tolerance of the cursor chromatogram, Da
+const TOLERANCE: f64 = 0.5;
+
+/// Heat map pane, retention time × mass to charge intensity image
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct HeatMapPane {
+    pub(crate) frame: HashedMetaDataFrame,
+    pub(crate) settings: Settings,
+    #[serde(skip)]
+    texture: Option<(u64, TextureHandle)>,
+    /// Retention time and mass to charge at the cursor
+    #[serde(skip)]
+    crosshair: Option<[f64; 2]>,
+}
+
+impl HeatMapPane {
+    pub(super) fn ui(&mut self, ui: &mut Ui) {
+        let id = ui.make_persistent_id("HeatMap");
+        let points = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<PointsComputed>()
+                .get(PointsKey::new(&self.frame.data))
+        });
+        // Bins follow the visible region of the previous frame
+        let bounds = match PlotMemory::load(ui.ctx(), id) {
+            Some(memory) => [memory.bounds().min(), memory.bounds().max()],
+            None => points.extent,
+        };
+        let value = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<HeatMapComputed>()
+                .get(HeatMapKey::new(&points, bounds, &self.settings))
+        });
+        let texture = match &mut self.texture {
+            Some((hash, texture)) => {
+                if *hash != value.hash {
+                    texture.set(value.image.clone(), TextureOptions::NEAREST);
+                    *hash = value.hash;
+                }
+                texture.clone()
+            }
+            None => {
+                let texture =
+                    ui.ctx()
+                        .load_texture("HeatMap", value.image.clone(), TextureOptions::NEAREST);
+                self.texture = Some((value.hash, texture.clone()));
+                texture
+            }
+        };
+        let [[left, bottom], [right, top]] = value.bounds;
+        // Mass to charge bin height
+        let tolerance =
+            ((top - bottom) / (2 * self.settings.heat_map.resolution.max(1)) as f64).max(TOLERANCE);
+        let link = link(&self.frame, &self.settings);
+        let scope = scope(&self.frame, &self.settings);
+        let selection = Selection::get(ui.ctx(), scope);
+        let shift = ui.input(|input| input.modifiers.shift);
+        let plot = Plot::new("HeatMap").id(id).allow_drag(!shift);
+        let response = link::plot(plot, link)
+            .height((ui.available_height() - HEIGHT).max(HEIGHT))
+            .x_axis_label("Retention time")
+            .y_axis_label("Mass to charge")
+            .show(ui, |ui| {
+                ui.image(PlotImage::new(
+                    "HeatMap",
+                    texture.id(),
+                    PlotPoint::new((left + right) / 2.0, (bottom + top) / 2.0),
+                    [(right - left) as f32, (top - bottom) as f32],
+                ));
+                if let Some([retention_time, mass_to_charge]) = self.crosshair {
+                    ui.vline(VLine::new("Retention time", retention_time));
+                    ui.hline(HLine::new("Mass to charge", mass_to_charge));
+                }
+                if let Some(selection) = selection {
+                    selection.show(ui);
+                }
+                ui.pointer_coordinate()
+            });
+        if let Some(cursor) = response.inner
+            && response.response.hovered()
+        {
+            self.crosshair = Some([cursor.x, cursor.y]);
+            if response.response.clicked() {
+                selection::select_mass_to_charge(
+                    ui.ctx(),
+                    scope,
+                    self.settings.binning.bin(cursor.y as _),
+                );
+            }
+        }
+        Selection::select(ui, scope, &response.response, response.inner);
+        // Linked spectrum and chromatogram at the cursor
+        let Some(crosshair) = self.crosshair else {
+            return;
+        };
+        let key = CrosshairKey::new(&points, crosshair, tolerance);
+        let mass_to_charge = key.mass_to_charge();
+        let value = ui.memory_mut(|memory| memory.caches.cache::<CrosshairComputed>().get(key));
+        ui.columns(2, |ui| {
+            Plot::new("HeatMapSpectrum")
+                .height(HEIGHT)
+                .x_axis_label(format!("Spectrum at {:.3}", key.scan.0))
+                .show(&mut ui[0], |ui| {
+                    let bars = value
+                        .spectrum
+                        .iter()
+                        .map(|&[mass_to_charge, signal]| Bar::new(mass_to_charge, signal))
+                        .collect();
+                    ui.bar_chart(BarChart::new("Spectrum", bars).width(0.1));
+                    ui.vline(VLine::new("Mass to charge", crosshair[1]));
+                });
+            Plot::new("HeatMapChromatogram")
+                .height(HEIGHT)
+                .x_axis_label(format!(
+                    "Chromatogram of {mass_to_charge:.1} ± {tolerance:.1}"
+                ))
+                .show(&mut ui[1], |ui| {
+                    ui.line(Line::new("Chromatogram", value.chromatogram.to_vec()));
+                    ui.vline(VLine::new("Retention time", crosshair[0]));
+                });
+        });
+    }
+}
@@ -1,60 +1,206 @@
-use self::{behavior::Behavior, plot::PlotPane, table::TablePane};
+use self::{
+    behavior::Behavior, comparison::ComparisonPane, heat_map::HeatMapPane, peaks::PeaksPane,
+    plot::PlotPane, search::SearchPane, spectrum::SpectrumPane, table::TablePane,
+};
 use crate::{
-    app::states::settings::{Settings, Sort, TimeUnits},
+    app::states::settings::{Kind, Settings, Sort, TimeUnits},
     utils::hash::{HashedDataFrame, HashedMetaDataFrame},
 };
 use egui::{ComboBox, DragValue, Ui};
-use egui_phosphor::regular::{CHART_BAR, TABLE};
-use egui_tiles::TileId;
+use egui_phosphor::regular::{
+    BARCODE, CHART_BAR, CHART_LINE, GRID_FOUR, MAGNIFYING_GLASS, STACK, TABLE,
+};
+use egui_tiles::{Tile, TileId, Tree};
+use itertools::Itertools;
 use polars::frame::DataFrame;
 use serde::{Deserialize, Serialize};
 
 /// Pane
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub(crate) enum Pane {
+    Comparison(ComparisonPane),
+    HeatMap(HeatMapPane),
+    Peaks(PeaksPane),
     Plot(PlotPane),
+    Search(SearchPane),
+    Spectrum(SpectrumPane),
     Table(TablePane),
 }
 
 impl Pane {
     pub(crate) const fn icon(&self) -> &str {
         match self {
+            Self::Comparison(_) => STACK,
+            Self::HeatMap(_) => GRID_FOUR,
+            Self::Peaks(_) => CHART_LINE,
             Self::Plot(_) => CHART_BAR,
+            Self::Search(_) => MAGNIFYING_GLASS,
+            Self::Spectrum(_) => BARCODE,
             Self::Table(_) => TABLE,
         }
     }
 
     pub(crate) const fn title(&self) -> &'static str {
         match self {
+            Self::Comparison(_) => "Comparison",
+            Self::HeatMap(_) => "Heat map",
+            Self::Peaks(_) => "Peaks",
             Self::Plot(_) => "Plot",
+            Self::Search(_) => "Search",
+            Self::Spectrum(_) => "Spectrum",
             Self::Table(_) => "Table",
         }
     }
 
     pub(crate) const fn frame(&self) -> &HashedMetaDataFrame {
         match self {
+            Self::Comparison(comparison) => &comparison.frame,
+            Self::HeatMap(heat_map) => &heat_map.frame,
+            Self::Peaks(peaks) => &peaks.frame,
             Self::Plot(plot) => &plot.frame,
+            Self::Search(search) => &search.frame,
+            Self::Spectrum(spectrum) => &spectrum.frame,
             Self::Table(table) => &table.frame,
         }
     }
+
+    pub(crate) const fn parts(&self) -> (&HashedMetaDataFrame, &Settings) {
+        match self {
+            Self::Comparison(comparison) => (&comparison.frame, &comparison.settings),
+            Self::HeatMap(heat_map) => (&heat_map.frame, &heat_map.settings),
+            Self::Peaks(peaks) => (&peaks.frame, &peaks.settings),
+            Self::Plot(plot) => (&plot.frame, &plot.settings),
+            Self::Search(search) => (&search.frame, &search.settings),
+            Self::Spectrum(spectrum) => (&spectrum.frame, &spectrum.settings),
+            Self::Table(table) => (&table.frame, &table.settings),
+        }
+    }
+
+    /// Link group of the pane
+    pub(crate) const fn link_mut(&mut self) -> &mut Option<usize> {
+        match self {
+            Self::Comparison(comparison) => &mut comparison.settings.link,
+            Self::HeatMap(heat_map) => &mut heat_map.settings.link,
+            Self::Peaks(peaks) => &mut peaks.settings.link,
+            Self::Plot(plot) => &mut plot.settings.link,
+            Self::Search(search) => &mut search.settings.link,
+            Self::Spectrum(spectrum) => &mut spectrum.settings.link,
+            Self::Table(table) => &mut table.settings.link,
+        }
+    }
+
+    /// Comparison pane of the same frame and settings
+    pub(crate) fn comparison(&self) -> Self {
+        let (frame, settings) = self.parts();
+        Self::Comparison(ComparisonPane {
+            frame: frame.clone(),
+            settings: settings.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Heat map pane of the same frame and settings
+    pub(crate) fn heat_map(&self) -> Self {
+        let (frame, settings) = self.parts();
+        Self::HeatMap(HeatMapPane {
+            frame: frame.clone(),
+            settings: settings.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Peaks pane of the same frame and settings
+    pub(crate) fn peaks(&self) -> Self {
+        let (frame, settings) = self.parts();
+        Self::Peaks(PeaksPane {
+            frame: frame.clone(),
+            settings: settings.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Plot pane of the same frame and settings
+    pub(crate) fn plot(&self) -> Self {
+        let (frame, settings) = self.parts();
+        Self::Plot(PlotPane {
+            frame: frame.clone(),
+            settings: settings.clone(),
+        })
+    }
+
+    /// Search pane of the same frame and settings
+    pub(crate) fn search(&self) -> Self {
+        let (frame, settings) = self.parts();
+        Self::Search(SearchPane {
+            frame: frame.clone(),
+            settings: settings.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Spectrum pane of the same frame and settings
+    pub(crate) fn spectrum(&self) -> Self {
+        let (frame, settings) = self.parts();
+        Self::Spectrum(SpectrumPane {
+            frame: frame.clone(),
+            settings: settings.clone(),
+        })
+    }
+
+    /// Table pane of the same frame and settings
+    pub(crate) fn table(&self) -> Self {
+        let (frame, settings) = self.parts();
+        Self::Table(TablePane {
+            frame: frame.clone(),
+            settings: settings.clone(),
+        })
+    }
 }
 
 impl Pane {
-    pub(crate) fn ui(&mut self, ui: &mut Ui) {
+    pub(crate) fn ui(&mut self, ui: &mut Ui, frames: &[HashedMetaDataFrame]) {
         match self {
+            Self::Comparison(comparison) => comparison.ui(ui, frames),
+            Self::HeatMap(heat_map) => heat_map.ui(ui),
+            Self::Peaks(peaks) => peaks.ui(ui),
             Self::Plot(plot) => plot.ui(ui),
+            Self::Search(search) => search.ui(ui),
+            Self::Spectrum(spectrum) => spectrum.ui(ui),
             Self::Table(table) => table.ui(ui),
         }
     }
 
     pub(crate) fn settings(&mut self, ui: &mut Ui) {
         match self {
-            Self::Plot(plot) => plot.settings.ui(ui),
-            Self::Table(table) => table.settings.ui(ui),
+            Self::Comparison(comparison) => comparison.settings.ui(ui, Kind::Comparison),
+            Self::HeatMap(heat_map) => heat_map.settings.ui(ui, Kind::HeatMap),
+            Self::Peaks(peaks) => peaks.settings.ui(ui, Kind::Peaks),
+            Self::Plot(plot) => plot.settings.ui(ui, Kind::Plot),
+            Self::Search(search) => search.settings.ui(ui, Kind::Search),
+            Self::Spectrum(spectrum) => spectrum.settings.ui(ui, Kind::Spectrum),
+            Self::Table(table) => table.settings.ui(ui, Kind::Table),
         }
     }
 }
 
+/// Distinct frames of all panes of the tree
+pub(crate) fn frames(tree: &Tree<Pane>) -> Vec<HashedMetaDataFrame> {
+    tree.tiles
+        .iter()
+        .filter_map(|(_, tile)| match tile {
+            Tile::Pane(pane) => Some(pane.frame()),
+            Tile::Container(_) => None,
+        })
+        .unique_by(|frame| frame.data.hash)
+        .cloned()
+        .collect()
+}
+
 pub(crate) mod behavior;
+pub(crate) mod comparison;
+pub(crate) mod heat_map;
+pub(crate) mod peaks;
 pub(crate) mod plot;
+pub(crate) mod search;
+pub(crate) mod spectrum;
 pub(crate) mod table;
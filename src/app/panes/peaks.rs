@@ -0,0 +1,276 @@
+use crate::{
+    app::{
+        calibration::Calibration,
+        computers::{
+            peaks::{Computed as PeaksComputed, Key as PeaksKey, Peak, chromatogram},
+            table::{Computed as TableComputed, Key as TableKey},
+        },
+        link::{self, link, scope},
+        selection::Selection,
+        states::settings::{Settings, Sort},
+    },
+    utils::hash::HashedMetaDataFrame,
+};
+use egui::{Align2, Button, Direction, DragValue, Layout, RichText, Ui};
+use egui_ext::color;
+use egui_extras::{Column, TableBuilder};
+use egui_phosphor::regular::TRASH;
+use egui_plot::{Legend, Line, LineStyle, Plot, PlotPoint, PlotPoints, PlotUi, Text};
+use metadata::NAME;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, iter::zip};
+use tracing::error;
+
+const COLUMN_COUNT: usize = 9;
+
+/// Total ion chromatogram peaks pane
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct PeaksPane {
+    pub(crate) frame: HashedMetaDataFrame,
+    pub(crate) settings: Settings,
+    /// Carbon numbers of the standard peaks, by peak key
+    pub(crate) carbon_numbers: BTreeMap<i64, u32>,
+    pub(crate) first_carbon_number: u32,
+    /// Hold-up time of the standard run, minutes
+    pub(crate) dead_time: f64,
+}
+
+impl PeaksPane {
+    pub(super) fn ui(&mut self, ui: &mut Ui) {
+        if let Err(error) = self.try_ui(ui) {
+            error!(%error);
+            ui.label(error.to_string());
+        }
+    }
+
+    fn try_ui(&mut self, ui: &mut Ui) -> PolarsResult<()> {
+        // Peaks are detected on the frame grouped by retention time
+        let settings = Settings {
+            explode: false,
+            sort: Sort::RetentionTime,
+            ..self.settings.clone()
+        };
+        let data_frame = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<TableComputed>()
+                .get(TableKey::new(&self.frame.data, &settings))
+        });
+        // Failed computation is notified
+        if data_frame.width() == 0 {
+            return Ok(());
+        }
+        let value = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<PeaksComputed>()
+                .get(PeaksKey::new(&data_frame, &settings))
+        });
+        let (retention_time, signal) = chromatogram(&data_frame)?;
+        let height = ui.available_height() / 2.0;
+        let link = link(&self.frame, &self.settings);
+        let scope = scope(&self.frame, &self.settings);
+        let selection = Selection::get(ui.ctx(), scope);
+        let shift = ui.input(|input| input.modifiers.shift);
+        let plot = Plot::new("Peaks")
+            .height(height)
+            .legend(Legend::default())
+            .allow_drag(!shift);
+        let response = link::plot(plot, link).show(ui, |ui| {
+            let points = zip(retention_time, signal)
+                .map(|(retention_time, signal)| [retention_time, signal])
+                .collect::<Vec<_>>();
+            ui.line(Line::new("Total ion chromatogram", points));
+            overlay(ui, &value.peaks);
+            if let Some(selection) = selection {
+                selection.show(ui);
+            }
+            ui.pointer_coordinate()
+        });
+        Selection::select(ui, scope, &response.response, response.inner);
+        ui.separator();
+        self.calibration(ui, &value.peaks);
+        ui.separator();
+        self.table(ui, &value.peaks);
+        Ok(())
+    }
+
+    /// Carbon number assignment of a standard run
+    fn calibration(&mut self, ui: &mut Ui, peaks: &[Peak]) {
+        ui.horizontal(|ui| {
+            ui.label("Standard");
+            ui.add(DragValue::new(&mut self.first_carbon_number).range(1..=u32::MAX))
+                .on_hover_text("Carbon number of the first peak");
+            if ui
+                .button("Assign")
+                .on_hover_text("Assign consecutive carbon numbers to the peaks")
+                .clicked()
+            {
+                self.carbon_numbers = peaks
+                    .iter()
+                    .zip(self.first_carbon_number..)
+                    .map(|(peak, carbon_number)| (key(peak), carbon_number))
+                    .collect();
+            }
+            if ui.button("Clear").clicked() {
+                self.carbon_numbers.clear();
+            }
+            ui.separator();
+            ui.label("Dead time");
+            ui.add(
+                DragValue::new(&mut self.dead_time)
+                    .range(0.0..=f64::MAX)
+                    .speed(0.01),
+            )
+            .on_hover_text("Hold-up time, minutes");
+            let points = peaks
+                .iter()
+                .filter_map(|peak| Some((peak.apex[0], *self.carbon_numbers.get(&key(peak))?)))
+                .collect::<Vec<_>>();
+            if ui
+                .add_enabled(points.len() >= 2, Button::new("Save calibration"))
+                .on_hover_text("Use the assigned peaks as the retention index calibration")
+                .clicked()
+            {
+                let name = self.frame.meta.get(NAME).cloned().unwrap_or_default();
+                Calibration::set(ui.ctx(), Calibration::new(name, self.dead_time, points));
+            }
+            if let Some(calibration) = Calibration::get(ui.ctx()) {
+                ui.separator();
+                ui.label(format!(
+                    "Calibrated by {} ({} standards)",
+                    calibration.name,
+                    calibration.points.len(),
+                ));
+                if ui
+                    .button(TRASH)
+                    .on_hover_text("Remove the calibration")
+                    .clicked()
+                {
+                    Calibration::clear(ui.ctx());
+                }
+            }
+        });
+    }
+
+    fn table(&mut self, ui: &mut Ui, peaks: &[Peak]) {
+        let height = ui.spacing().interact_size.y;
+        let calibration = Calibration::get(ui.ctx());
+        let interpolation = self.settings.retention_index.interpolation;
+        let retention_time_precision = self.settings.retention_time.precision;
+        let retention_time = |ui: &mut Ui, value: f64| {
+            ui.label(format!("{value:.retention_time_precision$}"))
+                .on_hover_text(format!("{value} min"));
+        };
+        let signal_precision = self.settings.signal.precision;
+        let signal = |ui: &mut Ui, value: f64| {
+            ui.label(format!("{value:.signal_precision$}"))
+                .on_hover_text(value.to_string());
+        };
+        TableBuilder::new(ui)
+            .id_salt("Peaks")
+            .cell_layout(Layout::centered_and_justified(Direction::LeftToRight))
+            .columns(Column::auto(), COLUMN_COUNT)
+            .auto_shrink(false)
+            .striped(true)
+            .header(height, |mut row| {
+                for text in [
+                    "Peak",
+                    "Retention time",
+                    "Start",
+                    "End",
+                    "Height",
+                    "Area",
+                    "Area, %",
+                    "Carbon number",
+                    "Retention index",
+                ] {
+                    row.col(|ui| {
+                        ui.heading(text);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(height, peaks.len(), |mut row| {
+                    let index = row.index();
+                    let peak = &peaks[index];
+                    row.col(|ui| {
+                        let response = ui.label((index + 1).to_string());
+                        if peak.skimmed {
+                            response.on_hover_text("Tangent skimmed rider");
+                        }
+                    });
+                    row.col(|ui| retention_time(ui, peak.apex[0]));
+                    row.col(|ui| retention_time(ui, peak.start[0]));
+                    row.col(|ui| retention_time(ui, peak.end[0]));
+                    row.col(|ui| signal(ui, peak.height));
+                    row.col(|ui| signal(ui, peak.area));
+                    row.col(|ui| signal(ui, peak.percent));
+                    // Carbon number, zero is unassigned
+                    row.col(|ui| {
+                        let mut carbon_number =
+                            self.carbon_numbers.get(&key(peak)).copied().unwrap_or(0);
+                        let response = ui.add(DragValue::new(&mut carbon_number).custom_formatter(
+                            |value, _| {
+                                if value == 0.0 {
+                                    String::new()
+                                } else {
+                                    format!("C{value}")
+                                }
+                            },
+                        ));
+                        if response.changed() {
+                            if carbon_number == 0 {
+                                self.carbon_numbers.remove(&key(peak));
+                            } else {
+                                self.carbon_numbers.insert(key(peak), carbon_number);
+                            }
+                        }
+                    });
+                    row.col(|ui| {
+                        if let Some(retention_index) =
+                            calibration.as_ref().and_then(|calibration| {
+                                calibration.retention_index(peak.apex[0], interpolation)
+                            })
+                        {
+                            ui.label(format!("{retention_index:.0}"))
+                                .on_hover_text(retention_index.to_string());
+                        }
+                    });
+                });
+            });
+    }
+}
+
+/// Peak key, the apex retention time in milliseconds
+fn key(peak: &Peak) -> i64 {
+    (peak.apex[0] * 60_000.0).round() as _
+}
+
+/// Integration overlay: peak curve, baseline, drop lines and apex label
+pub(crate) fn overlay(ui: &mut PlotUi, peaks: &[Peak]) {
+    for (index, peak) in peaks.iter().enumerate() {
+        let name = format!("Peak {}", index + 1);
+        let color = color(index);
+        ui.line(Line::new(&name, PlotPoints::from(peak.points.clone())).color(color));
+        ui.line(
+            Line::new(&name, vec![peak.start, peak.end])
+                .color(color)
+                .style(LineStyle::dashed_dense()),
+        );
+        if let (Some(&first), Some(&last)) = (peak.points.first(), peak.points.last()) {
+            ui.line(Line::new(&name, vec![peak.start, first]).color(color));
+            ui.line(Line::new(&name, vec![peak.end, last]).color(color));
+        }
+        ui.text(
+            Text::new(
+                &name,
+                PlotPoint::new(peak.apex[0], peak.apex[1]),
+                RichText::new((index + 1).to_string()).small(),
+            )
+            .color(color)
+            .anchor(Align2::CENTER_BOTTOM),
+        );
+    }
+}
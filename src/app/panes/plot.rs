@@ -1,10 +1,18 @@
 use crate::{
     app::{
+        calibration::Calibration,
         computers::{
+            exploded::{Computed as ExplodedComputed, Key as ExplodedKey, LEVELS},
+            extraction::{Computed as ExtractionComputed, Key as ExtractionKey},
+            peaks::{Computed as PeaksComputed, Key as PeaksKey},
             plot::{Computed as PlotComputed, Key as PlotKey},
             table::{Computed as TableComputed, Key as TableKey},
         },
-        states::settings::{Settings, Sort},
+        link::{self, link, scope},
+        notifications,
+        panes::peaks::overlay,
+        selection::{self, Selection},
+        states::settings::{Axis, Settings, Sort},
     },
     r#const::*,
     utils::hash::{HashedDataFrame, HashedMetaDataFrame},
@@ -15,11 +23,12 @@ use egui::{
 };
 use egui_ext::color;
 use egui_plot::{
-    Bar, BarChart, HLine, Legend, Line, Plot, PlotMemory, PlotPoint, PlotPoints, Text,
+    AxisHints, Bar, BarChart, HLine, Legend, Line, Plot, PlotMemory, PlotPoint, PlotPoints, Points,
+    Text,
 };
 use indexmap::IndexMap;
 use itertools::Itertools;
-use polars::{error::PolarsResult, frame::DataFrame};
+use polars::prelude::{DataFrame, DataType, PolarsResult};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, HashMap},
@@ -29,6 +38,9 @@ use std::{
 };
 use tracing::error;
 
+/// Milliseconds in a minute
+const MINUTES: f64 = 60_000.0;
+
 /// Plot pane
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub(crate) struct PlotPane {
@@ -40,23 +52,172 @@ pub(crate) struct PlotPane {
 impl PlotPane {
     pub(super) fn ui(&mut self, ui: &mut Ui) {
         match self.settings.sort {
+            _ if self.settings.extraction.plot => self.extracted_ion_chromatograms(ui),
             Sort::RetentionTime if !self.settings.explode => self.grouped_by_retention_time(ui),
-            Sort::MassToCharge if !self.settings.explode => self.grouped_by_mass_to_charge(ui),
-            _ => unimplemented!(),
+            Sort::MassToCharge if !self.settings.explode => {
+                if let Err(error) = self.grouped_by_mass_to_charge(ui) {
+                    notifications::error("Plot", error);
+                }
+            }
+            _ => self.exploded(ui),
+        }
+    }
+
+    /// Every point of the exploded frame, retention time against mass to
+    /// charge, the signal is mapped to the color and size of the point
+    pub(super) fn exploded(&self, ui: &mut Ui) {
+        let frame = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<TableComputed>()
+                .get(TableKey::new(&self.frame.data, &self.settings))
+        });
+        // Failed computation is notified
+        if frame.width() == 0 {
+            return;
+        }
+        let value = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<ExplodedComputed>()
+                .get(ExplodedKey::new(&frame))
+        });
+        // Points outside the visible region of the previous frame are skipped
+        let id = ui.make_persistent_id("Exploded");
+        let bounds = PlotMemory::load(ui.ctx(), id).map(|memory| {
+            let bounds = memory.bounds();
+            [bounds.min(), bounds.max()]
+        });
+        let visible = |&[x, y]: &[f64; 2]| {
+            bounds.is_none_or(|[min, max]| min[0] <= x && x <= max[0] && min[1] <= y && y <= max[1])
+        };
+        let mut plot = Plot::new("Exploded")
+            .id(id)
+            .x_axis_label("Retention time")
+            .y_axis_label("Mass to charge")
+            .label_formatter(|name, value| {
+                let mut label = String::new();
+                if !name.is_empty() {
+                    _ = writeln!(&mut label, "Signal: {name}");
+                }
+                _ = writeln!(&mut label, "Retention time: {:.4}", value.x);
+                _ = write!(&mut label, "Mass to charge: {:.4}", value.y);
+                label
+            });
+        if self.settings.plot.legend {
+            plot = plot.legend(Legend::default());
+        }
+        let link = link(&self.frame, &self.settings);
+        let scope = scope(&self.frame, &self.settings);
+        let selection = Selection::get(ui.ctx(), scope);
+        let selected = selection::mass_to_charge(ui.ctx(), scope);
+        let shift = ui.input(|input| input.modifiers.shift);
+        let response = link::plot(plot, link).allow_drag(!shift).show(ui, |ui| {
+            for (level, points) in value.levels.iter().enumerate() {
+                let points = points
+                    .iter()
+                    .copied()
+                    .filter(|point| visible(point))
+                    .collect::<Vec<_>>();
+                if points.is_empty() {
+                    continue;
+                }
+                let fraction = level as f32 / (LEVELS - 1) as f32;
+                let name = format!("≥ {:.0}", value.signal(level));
+                ui.points(
+                    Points::new(name, points)
+                        .color(self.settings.heat_map.colormap.color(fraction))
+                        .radius(1.0 + 2.0 * fraction),
+                );
+            }
+            if let Some(selection) = selection {
+                selection.show(ui);
+            }
+            if let Some(selected) = selected {
+                ui.hline(HLine::new("Mass to charge", selected));
+            }
+            ui.pointer_coordinate()
+        });
+        Selection::select(ui, scope, &response.response, response.inner);
+    }
+
+    /// Extracted ion chromatograms of the ions in the settings
+    pub(super) fn extracted_ion_chromatograms(&self, ui: &mut Ui) {
+        // Extracted from the frame grouped by mass to charge
+        let settings = Settings {
+            explode: false,
+            sort: Sort::MassToCharge,
+            ..self.settings.clone()
+        };
+        let frame = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<TableComputed>()
+                .get(TableKey::new(&self.frame.data, &settings))
+        });
+        // Failed computation is notified
+        if frame.width() == 0 {
+            return;
         }
+        let value = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<ExtractionComputed>()
+                .get(ExtractionKey::new(&frame, &settings))
+        });
+        let mut plot = Plot::new("ExtractedIonChromatograms");
+        if settings.plot.legend {
+            plot = plot.legend(Legend::default());
+        }
+        let link = link(&self.frame, &self.settings);
+        let scope = scope(&self.frame, &self.settings);
+        let selection = Selection::get(ui.ctx(), scope);
+        let shift = ui.input(|input| input.modifiers.shift);
+        let response = link::plot(plot, link).allow_drag(!shift).show(ui, |ui| {
+            for trace in value.traces {
+                ui.line(Line::new(trace.name, trace.points).color(trace.color));
+            }
+            if let Some(selection) = selection {
+                selection.show(ui);
+            }
+            ui.pointer_coordinate()
+        });
+        Selection::select(ui, scope, &response.response, response.inner);
     }
 
-    pub(super) fn grouped_by_mass_to_charge(&self, ui: &mut Ui) {
+    pub(super) fn grouped_by_mass_to_charge(&self, ui: &mut Ui) -> PolarsResult<()> {
         let data_frame = ui.memory_mut(|memory| {
             memory
                 .caches
                 .cache::<TableComputed>()
                 .get(TableKey::new(&self.frame.data, &self.settings))
         });
-        // let points = data_frame.height();
-        let mass_to_charge = data_frame[MASS_TO_CHARGE].f32().unwrap();
-        let retention_time = data_frame[RETENTION_TIME].list().unwrap();
-        let signal = data_frame[SIGNAL].list().unwrap();
+        // Failed computation is notified
+        if data_frame.width() == 0 {
+            return Ok(());
+        }
+        // Retention time (minutes) and signal of each extracted ion
+        let mut lines = Vec::with_capacity(data_frame.height());
+        for (mass_to_charge, ion_chromatogram) in zip(
+            data_frame[MASS_TO_CHARGE].f32()?,
+            data_frame["ExtractedIonChromatogram"].list()?,
+        ) {
+            let (Some(mass_to_charge), Some(ion_chromatogram)) = (mass_to_charge, ion_chromatogram)
+            else {
+                continue;
+            };
+            let ion_chromatogram = ion_chromatogram.struct_()?;
+            let retention_time = ion_chromatogram
+                .field_by_name(RETENTION_TIME)?
+                .cast(&DataType::Float64)?;
+            let signal = ion_chromatogram
+                .field_by_name(SIGNAL)?
+                .cast(&DataType::Float64)?;
+            let points = zip(retention_time.f64()?, signal.f64()?)
+                .filter_map(|(retention_time, signal)| Some([retention_time? / MINUTES, signal?]))
+                .collect::<Vec<_>>();
+            lines.push((mass_to_charge, points));
+        }
         ui.vertical_centered_justified(|ui| {
             // let id = ui.make_persistent_id("plot");
             // let plot_memory = PlotMemory::load(ui.ctx(), id);
@@ -76,31 +237,44 @@ impl PlotPane {
                 // }
                 plot = plot.legend(legend);
             }
-            plot.show(ui, |ui| {
+            let link = link(&self.frame, &self.settings);
+            let scope = scope(&self.frame, &self.settings);
+            let selection = Selection::get(ui.ctx(), scope);
+            let selected = selection::mass_to_charge(ui.ctx(), scope);
+            let shift = ui.input(|input| input.modifiers.shift);
+            let response = link::plot(plot, link).allow_drag(!shift).show(ui, |ui| {
                 // let bounds = ui.plot_bounds().range_x();
                 // let width = ui.plot_bounds().width();
                 // tracing::error!(?width);
 
                 // Lines
-                for (mass_to_charge, retention_time, signal) in
-                    zip(mass_to_charge, zip(retention_time, signal)).filter_map(
-                        |(mass_to_charge, (retention_time, signal))| {
-                            Some((mass_to_charge?, retention_time?, signal?))
-                        },
-                    )
-                {
-                    let line = Line::new(
-                        mass_to_charge.to_string(),
-                        PlotPoints::from_iter(
-                            zip(retention_time.i32().unwrap(), signal.u16().unwrap()).filter_map(
-                                |(retention_time, signal)| {
-                                    Some([retention_time? as _, signal? as _])
-                                },
-                            ),
-                        ),
-                    );
+                let clicked = ui
+                    .response()
+                    .clicked()
+                    .then(|| ui.pointer_coordinate())
+                    .flatten();
+                let width = ui.plot_bounds().width();
+                let height = ui.plot_bounds().height();
+                let mut nearest = None::<(f32, f64)>;
+                for (mass_to_charge, points) in lines {
+                    // Line nearest to the click
+                    if let Some(pointer) = clicked {
+                        for &[x, y] in &points {
+                            let distance = ((x - pointer.x) / width).powi(2)
+                                + ((y - pointer.y) / height).powi(2);
+                            if nearest.is_none_or(|(_, nearest)| distance < nearest) {
+                                nearest = Some((mass_to_charge, distance));
+                            }
+                        }
+                    }
+                    let line = Line::new(mass_to_charge.to_string(), PlotPoints::from(points))
+                        .highlight(selected == Some(mass_to_charge));
                     ui.line(line);
                 }
+                if let Some(selection) = selection {
+                    selection.show(ui);
+                }
+                (ui.pointer_coordinate(), nearest)
 
                 // // Bars
                 // for (mass_to_charge, retention_time, signal) in
@@ -224,7 +398,13 @@ impl PlotPane {
                 //     }
                 // }
             });
+            let (pointer, nearest) = response.inner;
+            if let Some((mass_to_charge, _)) = nearest {
+                selection::select_mass_to_charge(ui.ctx(), scope, mass_to_charge);
+            }
+            Selection::select(ui, scope, &response.response, pointer);
         });
+        Ok(())
     }
 
     pub(super) fn grouped_by_retention_time(&self, ui: &mut Ui) {
@@ -234,20 +414,69 @@ impl PlotPane {
                 .cache::<TableComputed>()
                 .get(TableKey::new(&self.frame.data, &self.settings))
         });
+        // Failed computation is notified
+        if frame.width() == 0 {
+            return;
+        }
         let value = ui.memory_mut(|memory| {
             memory
                 .caches
                 .cache::<PlotComputed>()
                 .get(PlotKey::new(&frame, &self.settings))
         });
-        let mut plot = Plot::new("Plot").label_formatter(|name, value| {
-            if !name.is_empty() {
-                format!("{}\nx: {}\ny: {}", name, value.x, value.y)
-            } else {
-                format!("x: {}\ny: {}", value.x, value.y)
-                // "".to_owned()
+        let peaks = self.settings.peaks.integrate.then(|| {
+            ui.memory_mut(|memory| {
+                memory
+                    .caches
+                    .cache::<PeaksComputed>()
+                    .get(PeaksKey::new(&frame, &self.settings))
+            })
+        });
+        let calibration = Calibration::get(ui.ctx());
+        let interpolation = self.settings.retention_index.interpolation;
+        let mut plot = Plot::new("Plot").label_formatter({
+            let calibration = calibration.clone();
+            move |name, value| {
+                let mut label = if !name.is_empty() {
+                    format!("{}\nx: {}\ny: {}", name, value.x, value.y)
+                } else {
+                    format!("x: {}\ny: {}", value.x, value.y)
+                    // "".to_owned()
+                };
+                if let Some(retention_index) = calibration
+                    .as_ref()
+                    .and_then(|calibration| calibration.retention_index(value.x, interpolation))
+                {
+                    _ = write!(&mut label, "\nRI: {retention_index:.0}");
+                }
+                label
             }
         });
+        // Retention index or equivalent chain length axis under the retention
+        // time axis
+        if let Some(calibration) = calibration
+            && self.settings.retention_index.axis != Axis::RetentionTime
+        {
+            let axis = self.settings.retention_index.axis;
+            plot = plot.custom_x_axes(vec![
+                AxisHints::new_x().label("Retention time"),
+                AxisHints::new_x()
+                    .label(axis.text())
+                    .formatter(move |mark, _| {
+                        let value = match axis {
+                            Axis::RetentionIndex => {
+                                calibration.retention_index(mark.value, interpolation)
+                            }
+                            _ => calibration.equivalent_chain_length(mark.value, interpolation),
+                        };
+                        match value {
+                            Some(value) if axis == Axis::RetentionIndex => format!("{value:.0}"),
+                            Some(value) => format!("{value:.2}"),
+                            None => String::new(),
+                        }
+                    }),
+            ]);
+        }
         // .label_formatter(move |name, PlotPoint { x, y }| {
         //             let mut label = String::new();
         //             if !name.is_empty() {
@@ -291,7 +520,13 @@ impl PlotPane {
             // }
             plot = plot.legend(legend);
         }
-        plot.show(ui, |ui| {
+        // Click selects a retention time, shift drag selects a range
+        let link = link(&self.frame, &self.settings);
+        let scope = scope(&self.frame, &self.settings);
+        let selection = Selection::get(ui.ctx(), scope);
+        let selected = selection::mass_to_charge(ui.ctx(), scope);
+        let shift = ui.input(|input| input.modifiers.shift);
+        let response = link::plot(plot, link).allow_drag(!shift).show(ui, |ui| {
             let range_x = ui.plot_bounds().range_x();
             // let width = ui.plot_bounds().width();
             // tracing::error!(?width);
@@ -303,6 +538,7 @@ impl PlotPane {
                 let index = mass_to_charge.0.round() as usize;
                 let bar_chart = BarChart::new("Bar chart", bars)
                     .color(color(index))
+                    .highlight(selected == Some(mass_to_charge.0))
                     .element_formatter(Box::new(move |bar, _bar_chart| {
                         let mut label = String::new();
                         _ = writeln!(&mut label, "Retention time (x): {}", bar.argument);
@@ -343,6 +579,21 @@ impl PlotPane {
             if !value.rolling_mean.is_empty() {
                 ui.line(Line::new("Rolling mean", value.rolling_mean));
             }
+            // Baseline
+            if !value.baseline.is_empty() {
+                ui.line(Line::new("Baseline", value.baseline));
+                ui.line(Line::new("Corrected", value.corrected));
+            }
+            // Integration
+            if let Some(peaks) = &peaks {
+                overlay(ui, &peaks.peaks);
+            }
+            // Selection
+            if let Some(selection) = selection {
+                selection.show(ui);
+            }
+            ui.pointer_coordinate()
         });
+        Selection::select(ui, scope, &response.response, response.inner);
     }
 }
@@ -0,0 +1,214 @@
+use crate::{
+    app::{
+        computers::{
+            search::{Computed as SearchComputed, Hit, Key as SearchKey},
+            table::{Computed as TableComputed, Key as TableKey},
+        },
+        library::Library,
+        link::scope,
+        selection::Selection,
+        states::settings::{Settings, Sort},
+    },
+    r#const::*,
+    utils::hash::HashedMetaDataFrame,
+};
+use egui::{Color32, Direction, Layout, Sense, Ui};
+use egui_extras::{Column, TableBuilder};
+use egui_phosphor::regular::TRASH;
+use egui_plot::{Bar, BarChart, HLine, Legend, Plot};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+/// Number of columns of the hits table
+const COLUMN_COUNT: usize = 5;
+/// Mirror plot signal scale
+const SCALE: f64 = 999.0;
+
+/// Library search pane
+///
+/// Searches the spectrum of the scan nearest to the selected retention time.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct SearchPane {
+    pub(crate) frame: HashedMetaDataFrame,
+    pub(crate) settings: Settings,
+    /// Selected hit
+    pub(crate) hit: usize,
+}
+
+impl SearchPane {
+    pub(super) fn ui(&mut self, ui: &mut Ui) {
+        let Some(library) = Library::get(ui.ctx()) else {
+            ui.centered_and_justified(|ui| {
+                ui.label("Drop a NIST MSP library to search");
+            });
+            return;
+        };
+        if let Err(error) = self.search(ui, &library) {
+            error!(%error);
+            ui.label(error.to_string());
+        }
+    }
+
+    fn search(&mut self, ui: &mut Ui, library: &Library) -> PolarsResult<()> {
+        // Search works on spectra, i.e. the frame grouped by retention time
+        let settings = Settings {
+            explode: false,
+            sort: Sort::RetentionTime,
+            ..self.settings.clone()
+        };
+        let data_frame = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<TableComputed>()
+                .get(TableKey::new(&self.frame.data, &settings))
+        });
+        // Failed computation is notified
+        if data_frame.width() == 0 {
+            return Ok(());
+        }
+        let retention_time = data_frame[RETENTION_TIME].f64()?;
+        // Scan nearest to the selected retention time
+        let selection = Selection::get(ui.ctx(), scope(&self.frame, &self.settings));
+        let index = selection.and_then(|selection| {
+            retention_time
+                .iter()
+                .enumerate()
+                .filter_map(|(index, retention_time)| Some((index, retention_time?)))
+                .min_by(|left, right| {
+                    (left.1 - selection.start)
+                        .abs()
+                        .total_cmp(&(right.1 - selection.start).abs())
+                })
+        });
+        ui.horizontal(|ui| {
+            ui.label("Retention time");
+            match index {
+                Some((_, retention_time)) => {
+                    let precision = self.settings.retention_time.precision;
+                    ui.label(format!("{retention_time:.precision$}"))
+                        .on_hover_text("Searched spectrum (minutes)");
+                }
+                None => {
+                    ui.label("Select a retention time to search");
+                }
+            }
+            ui.label(format!("{} entries", library.entries.len()));
+            if ui
+                .button(TRASH)
+                .on_hover_text("Unload the library")
+                .clicked()
+            {
+                Library::clear(ui.ctx());
+            }
+        });
+        let Some((index, _)) = index else {
+            return Ok(());
+        };
+        let value = ui.memory_mut(|memory| {
+            memory.caches.cache::<SearchComputed>().get(SearchKey::new(
+                &data_frame,
+                index,
+                library,
+                &settings,
+            ))
+        });
+        if value.hits.is_empty() {
+            ui.label("No hits");
+            return Ok(());
+        }
+        self.hit = self.hit.min(value.hits.len() - 1);
+        self.hits(ui, library, &value.hits);
+        ui.separator();
+        let hit = &value.hits[self.hit];
+        mirror(ui, &value.spectrum, &library.entries[hit.index].peaks);
+        Ok(())
+    }
+
+    fn hits(&mut self, ui: &mut Ui, library: &Library, hits: &[Hit]) {
+        let height = ui.spacing().interact_size.y;
+        TableBuilder::new(ui)
+            .id_salt("Hits")
+            .cell_layout(Layout::centered_and_justified(Direction::LeftToRight))
+            .column(Column::auto())
+            .column(Column::remainder())
+            .columns(Column::auto(), COLUMN_COUNT - 2)
+            .auto_shrink([false, true])
+            .max_scroll_height(height * 8.0)
+            .sense(Sense::click())
+            .striped(true)
+            .header(height, |mut row| {
+                for text in ["#", "Name", "Forward", "Reverse", "Formula"] {
+                    row.col(|ui| {
+                        ui.heading(text);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(height, hits.len(), |mut row| {
+                    let rank = row.index();
+                    let hit = &hits[rank];
+                    let entry = &library.entries[hit.index];
+                    row.set_selected(rank == self.hit);
+                    row.col(|ui| {
+                        ui.label((rank + 1).to_string());
+                    });
+                    row.col(|ui| {
+                        let response = ui.label(&entry.name);
+                        if let Some(cas) = &entry.cas {
+                            response.on_hover_text(format!("CAS {cas}"));
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(format!("{:.0}", hit.forward));
+                    });
+                    row.col(|ui| {
+                        ui.label(format!("{:.0}", hit.reverse));
+                    });
+                    row.col(|ui| {
+                        ui.label(entry.formula.as_deref().unwrap_or_default());
+                    });
+                    if row.response().clicked() {
+                        self.hit = rank;
+                    }
+                });
+            });
+    }
+}
+
+/// Mirror plot, the searched spectrum up and the library spectrum down
+fn mirror(ui: &mut Ui, spectrum: &[(f32, f64)], peaks: &[(f32, f32)]) {
+    let bars = |peaks: Vec<(f64, f64)>, sign: f64| {
+        let max = peaks.iter().map(|&(_, signal)| signal).fold(0.0, f64::max);
+        peaks
+            .into_iter()
+            .filter(|_| max > 0.0)
+            .map(|(mass_to_charge, signal)| {
+                Bar::new(mass_to_charge, sign * SCALE * signal / max).width(0.5)
+            })
+            .collect::<Vec<_>>()
+    };
+    let unknown = bars(
+        spectrum
+            .iter()
+            .map(|&(mass_to_charge, signal)| (mass_to_charge as _, signal))
+            .collect(),
+        1.0,
+    );
+    let library = bars(
+        peaks
+            .iter()
+            .map(|&(mass_to_charge, signal)| (mass_to_charge as _, signal as _))
+            .collect(),
+        -1.0,
+    );
+    Plot::new("Mirror")
+        .legend(Legend::default())
+        .x_axis_label(MASS_TO_CHARGE)
+        .y_axis_label(SIGNAL)
+        .show(ui, |ui| {
+            ui.bar_chart(BarChart::new("Spectrum", unknown).color(Color32::LIGHT_BLUE));
+            ui.bar_chart(BarChart::new("Library", library).color(Color32::LIGHT_RED));
+            ui.hline(HLine::new("", 0.0).color(Color32::GRAY));
+        });
+}
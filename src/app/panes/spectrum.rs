@@ -0,0 +1,84 @@
+use crate::{
+    app::{
+        computers::spectrum::{Computed as SpectrumComputed, Key as SpectrumKey},
+        link::scope,
+        selection::Selection,
+        states::settings::Settings,
+    },
+    utils::hash::HashedMetaDataFrame,
+};
+use egui::{Align2, RichText, Ui};
+use egui_plot::{Bar, BarChart, Plot, PlotPoint, Text};
+use serde::{Deserialize, Serialize};
+
+/// Stick width, Da
+const WIDTH: f64 = 0.2;
+
+/// Mass spectrum pane, shows the spectrum at the selected retention time or
+/// the mean spectrum of the selected range
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct SpectrumPane {
+    pub(crate) frame: HashedMetaDataFrame,
+    pub(crate) settings: Settings,
+}
+
+impl SpectrumPane {
+    pub(super) fn ui(&self, ui: &mut Ui) {
+        let scope = scope(&self.frame, &self.settings);
+        let Some(selection) = Selection::get(ui.ctx(), scope) else {
+            ui.label(
+                "Click the chromatogram of a plot pane to select a retention time, shift drag to select a range",
+            );
+            return;
+        };
+        let value = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<SpectrumComputed>()
+                .get(SpectrumKey::new(
+                    &self.frame.data,
+                    selection,
+                    &self.settings,
+                ))
+        });
+        let precision = self.settings.retention_time.precision;
+        if selection.is_range() {
+            let mut text = format!(
+                "Retention time {:.precision$}-{:.precision$}, {} scans",
+                selection.start, selection.end, value.scans,
+            );
+            if self.settings.spectrum.subtraction {
+                text += &format!(", {} background scans subtracted", value.background);
+            }
+            ui.label(text);
+        } else {
+            ui.label(format!("Retention time {:.precision$}", selection.start));
+        }
+        // Highest peaks are labeled
+        let mut labeled = value.points.clone();
+        labeled.sort_by(|left, right| right[1].total_cmp(&left[1]));
+        labeled.truncate(self.settings.spectrum.labels);
+        let precision = self.settings.mass_to_charge.precision;
+        Plot::new("Spectrum")
+            .x_axis_label("Mass to charge")
+            .y_axis_label("Signal")
+            .show(ui, |ui| {
+                let bars = value
+                    .points
+                    .iter()
+                    .map(|&[mass_to_charge, signal]| Bar::new(mass_to_charge, signal))
+                    .collect();
+                ui.bar_chart(BarChart::new("Spectrum", bars).width(WIDTH));
+                for [mass_to_charge, signal] in labeled {
+                    ui.text(
+                        Text::new(
+                            "Labels",
+                            PlotPoint::new(mass_to_charge, signal),
+                            RichText::new(format!("{mass_to_charge:.precision$}")).small(),
+                        )
+                        .anchor(Align2::CENTER_BOTTOM),
+                    );
+                }
+            });
+    }
+}
@@ -1,13 +1,20 @@
 use crate::{
     app::{
-        computers::table::{Computed as TableComputed, Key as TableKey},
-        states::settings::{Settings, Sort, TimeUnits},
+        calibration::Calibration,
+        computers::{
+            fame::{Computed as FameComputed, Key as FameKey},
+            peaks::{Computed as PeaksComputed, Key as PeaksKey},
+            table::{Computed as TableComputed, Key as TableKey},
+        },
+        link::scope,
+        selection::{self, Selection},
+        states::settings::{Interpolation, Settings, Sort, TimeUnits},
         widgets::{ion_chromatogram::IonChromatogram, mass_spectrum::MassSpectrum},
     },
     r#const::*,
     utils::hash::HashedMetaDataFrame,
 };
-use egui::{Direction, Layout, Ui};
+use egui::{Direction, Layout, Sense, Ui, emath::Float};
 use egui_extras::{Column, TableBuilder};
 use polars::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -47,18 +54,24 @@ impl TablePane {
                 .cache::<TableComputed>()
                 .get(TableKey::new(&self.frame.data, &self.settings))
         });
+        // Failed computation is notified
+        if data_frame.width() == 0 {
+            return Ok(());
+        }
         let total_rows = data_frame.height();
-        // let mass_to_charge = .cast(&DataType::UInt32)?;
-        let mass_to_charge = data_frame[MASS_TO_CHARGE]
-            .as_materialized_series()
-            .round(2, RoundMode::HalfToEven)?;
-        let mass_to_charge = mass_to_charge.f32()?;
+        // Binned by the table computer
+        let mass_to_charge = data_frame[MASS_TO_CHARGE].f32()?;
+        // Selected mass to charge
+        let scope = scope(&self.frame, &self.settings);
+        let selected = selection::mass_to_charge(ui.ctx(), scope);
+        let mut clicked = None;
         TableBuilder::new(ui)
             .cell_layout(Layout::centered_and_justified(Direction::LeftToRight))
             .column(Column::auto_with_initial_suggestion(width))
             .columns(Column::auto(), COLUMN_COUNT - 1)
             .auto_shrink(false)
             .striped(true)
+            .sense(Sense::click())
             .header(height, |mut row| {
                 row.col(|ui| {
                     ui.heading("Index");
@@ -73,6 +86,8 @@ impl TablePane {
             .body(|body| {
                 body.rows(height, total_rows, |mut row| {
                     let row_index = row.index();
+                    let value = mass_to_charge.get(row_index);
+                    row.set_selected(value.is_some() && value == selected);
                     // Index
                     row.col(|ui| {
                         ui.label(row_index.to_string());
@@ -94,8 +109,14 @@ impl TablePane {
                             settings: &self.settings,
                         });
                     });
+                    if row.response().clicked() {
+                        clicked = value;
+                    }
                 });
             });
+        if let Some(mass_to_charge) = clicked {
+            selection::select_mass_to_charge(ui.ctx(), scope, mass_to_charge);
+        }
         Ok(())
     }
 
@@ -108,14 +129,68 @@ impl TablePane {
                 .cache::<TableComputed>()
                 .get(TableKey::new(&self.frame.data, &self.settings))
         });
+        // Failed computation is notified
+        if data_frame.width() == 0 {
+            return Ok(());
+        }
         let total_rows = data_frame.height();
         let retention_time = data_frame[RETENTION_TIME].as_materialized_series();
+        // FAME proposed at the peak apexes
+        let peaks = ui.memory_mut(|memory| {
+            memory
+                .caches
+                .cache::<PeaksComputed>()
+                .get(PeaksKey::new(&data_frame, &self.settings))
+        });
+        let apexes = peaks
+            .peaks
+            .iter()
+            .map(|peak| peak.apex[0].ord())
+            .collect::<Vec<_>>();
+        let fames = ui.memory_mut(|memory| {
+            memory.caches.cache::<FameComputed>().get(FameKey {
+                frame: &data_frame,
+                apexes: &apexes,
+            })
+        });
+        // Retention index and equivalent chain length of the calibrated runs
+        let calibration = Calibration::get(ui.ctx());
+        let minutes = retention_time.f64()?;
+        let interpolation = self.settings.retention_index.interpolation;
+        let calibrated =
+            |row_index: usize, f: fn(&Calibration, f64, Interpolation) -> Option<f64>| {
+                f(
+                    calibration.as_ref()?,
+                    minutes.get(row_index)?,
+                    interpolation,
+                )
+            };
+        let columns = if calibration.is_some() { 3 } else { 1 };
+        // Selected retention time, the nearest scan of a single one
+        let scope = scope(&self.frame, &self.settings);
+        let selection = Selection::get(ui.ctx(), scope);
+        let nearest = selection
+            .filter(|selection| !selection.is_range())
+            .and_then(|selection| {
+                minutes
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(row_index, minutes)| Some((row_index, minutes?)))
+                    .min_by(|left, right| {
+                        (left.1 - selection.start)
+                            .abs()
+                            .total_cmp(&(right.1 - selection.start).abs())
+                    })
+                    .map(|(row_index, _)| row_index)
+            });
+        let mut clicked = None;
         TableBuilder::new(ui)
             .cell_layout(Layout::centered_and_justified(Direction::LeftToRight))
             .column(Column::auto_with_initial_suggestion(width))
-            .columns(Column::auto(), COLUMN_COUNT - 1)
+            .columns(Column::auto(), COLUMN_COUNT - 1 + columns)
             .auto_shrink(false)
             .striped(true)
+            .sense(Sense::click())
             .header(height, |mut row| {
                 row.col(|ui| {
                     ui.heading("Index");
@@ -123,6 +198,18 @@ impl TablePane {
                 row.col(|ui| {
                     ui.heading("Retention time");
                 });
+                if calibration.is_some() {
+                    row.col(|ui| {
+                        ui.heading("RI").on_hover_text("Retention index");
+                    });
+                    row.col(|ui| {
+                        ui.heading("ECL").on_hover_text("Equivalent chain length");
+                    });
+                }
+                row.col(|ui| {
+                    ui.heading("FAME")
+                        .on_hover_text("Fatty acid methyl ester proposed at the peak apexes");
+                });
                 row.col(|ui| {
                     ui.heading("MassSpectrum");
                 });
@@ -130,6 +217,13 @@ impl TablePane {
             .body(|body| {
                 body.rows(height, total_rows, |mut row| {
                     let row_index = row.index();
+                    let value = minutes.get(row_index);
+                    row.set_selected(match selection {
+                        Some(selection) if selection.is_range() => {
+                            value.is_some_and(|value| selection.contains(value))
+                        }
+                        _ => nearest == Some(row_index),
+                    });
                     // Index
                     row.col(|ui| {
                         ui.label(row_index.to_string());
@@ -142,6 +236,37 @@ impl TablePane {
                         //     ui.label(formated).on_hover_text(formated.precision(None));
                         // }
                     });
+                    if calibration.is_some() {
+                        // Retention index
+                        row.col(|ui| {
+                            if let Some(value) = calibrated(row_index, Calibration::retention_index)
+                            {
+                                ui.label(format!("{value:.0}"))
+                                    .on_hover_text(value.to_string());
+                            }
+                        });
+                        // Equivalent chain length
+                        row.col(|ui| {
+                            if let Some(value) =
+                                calibrated(row_index, Calibration::equivalent_chain_length)
+                            {
+                                ui.label(format!("{value:.2}"))
+                                    .on_hover_text(value.to_string());
+                            }
+                        });
+                    }
+                    // FAME
+                    row.col(|ui| {
+                        if let Some(fame) = fames.fames.get(&row_index) {
+                            ui.label(fame.to_string()).on_hover_ui(|ui| {
+                                ui.label(format!("{} FAME", fame.class.text()));
+                                ui.label(format!("Confidence: {:.0}%", 100.0 * fame.confidence));
+                                for (mass_to_charge, signal) in &fame.ions {
+                                    ui.label(format!("m/z {mass_to_charge}: {signal:.1}%"));
+                                }
+                            });
+                        }
+                    });
                     // Mass spectrum
                     row.col(|ui| {
                         ui.add(MassSpectrum {
@@ -150,8 +275,18 @@ impl TablePane {
                             settings: &self.settings,
                         });
                     });
+                    if row.response().clicked() {
+                        clicked = value;
+                    }
                 });
             });
+        if let Some(retention_time) = clicked {
+            Selection::set(
+                ui.ctx(),
+                scope,
+                Selection::new(retention_time, retention_time),
+            );
+        }
         Ok(())
     }
 
@@ -164,6 +299,10 @@ impl TablePane {
                 .cache::<TableComputed>()
                 .get(TableKey::new(&self.frame.data, &self.settings))
         });
+        // Failed computation is notified
+        if data_frame.width() == 0 {
+            return Ok(());
+        }
         let total_rows = data_frame.height();
         let retention_time = data_frame[RETENTION_TIME].i32()?;
         let mass_to_charge = data_frame[MASS_TO_CHARGE].f32()?;
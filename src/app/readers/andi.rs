@@ -0,0 +1,228 @@
+use super::{Builder, INSTRUMENT, SOURCE, milliseconds, netcdf::File};
+use crate::{app::states::settings::TimeUnits, utils::hash::HashedMetaDataFrame};
+use anyhow::{Context as _, Result, ensure};
+use metadata::{DATE, Metadata, NAME};
+use std::iter::zip;
+use tracing::trace;
+
+// ASTM E2077 (ANDI-MS, AIA)
+
+const SCAN_ACQUISITION_TIME: &str = "scan_acquisition_time";
+const SCAN_INDEX: &str = "scan_index";
+const POINT_COUNT: &str = "point_count";
+const MASS_VALUES: &str = "mass_values";
+const INTENSITY_VALUES: &str = "intensity_values";
+
+/// Reads an ANDI-MS netCDF file into a long frame
+pub(crate) fn read(bytes: &[u8]) -> Result<HashedMetaDataFrame> {
+    let file = File::new(bytes)?;
+    let scan_acquisition_time = file.values(SCAN_ACQUISITION_TIME)?;
+    let point_count = file.values(POINT_COUNT)?;
+    let mass_values = file.values(MASS_VALUES)?;
+    let intensity_values = file.values(INTENSITY_VALUES)?;
+    ensure!(
+        scan_acquisition_time.len() == point_count.len(),
+        "ANDI-MS has {} scan times, but {} point counts",
+        scan_acquisition_time.len(),
+        point_count.len(),
+    );
+    ensure!(
+        mass_values.len() == intensity_values.len(),
+        "ANDI-MS has {} mass values, but {} intensity values",
+        mass_values.len(),
+        intensity_values.len(),
+    );
+    let scan_index = match file.values(SCAN_INDEX) {
+        Ok(scan_index) if scan_index.len() == point_count.len() => scan_index,
+        _ => point_count
+            .iter()
+            .scan(0.0, |start, count| {
+                let index = *start;
+                *start += count;
+                Some(index)
+            })
+            .collect(),
+    };
+    let units = file
+        .variable(SCAN_ACQUISITION_TIME)
+        .and_then(|variable| variable.attribute("units"))
+        .and_then(|units| units.text())
+        .map_or(TimeUnits::Second, time_units);
+    let mut builder = Builder::default();
+    for (retention_time, (start, count)) in zip(scan_acquisition_time, zip(scan_index, point_count))
+    {
+        let (start, count) = (index(start)?, index(count)?);
+        let end = start
+            .checked_add(count)
+            .with_context(|| format!("ANDI-MS scan at {retention_time} overflows"))?;
+        ensure!(
+            end <= mass_values.len(),
+            "ANDI-MS scan at {retention_time} exceeds {} points",
+            mass_values.len(),
+        );
+        builder.scan(
+            milliseconds(retention_time, units),
+            mass_values[start..end].iter().copied(),
+            intensity_values[start..end].iter().copied(),
+        );
+    }
+    let meta = metadata(&file);
+    trace!(?meta);
+    builder.build(meta)
+}
+
+/// Scan index or point count, a whole non-negative number
+fn index(value: f64) -> Result<usize> {
+    ensure!(
+        value.is_finite() && value >= 0.0 && value.fract() == 0.0 && value < usize::MAX as f64,
+        "ANDI-MS scan index or point count {value} is invalid",
+    );
+    Ok(value as _)
+}
+
+fn metadata(file: &File) -> Metadata {
+    let text = |name| {
+        file.attribute(name)
+            .and_then(|value| value.text())
+            .filter(|text| !text.is_empty())
+            .map(ToOwned::to_owned)
+    };
+    let mut meta = Metadata::default();
+    if let Some(name) = text("sample_name")
+        .or_else(|| text("sample_id"))
+        .or_else(|| text("experiment_title"))
+    {
+        meta.insert(NAME.to_owned(), name);
+    }
+    if let Some(date) = text("experiment_date_time_stamp").and_then(|stamp| date(&stamp)) {
+        meta.insert(DATE.to_owned(), date);
+    }
+    if let Some(instrument) = text("instrument_name")
+        .or_else(|| file.text("instrument_name"))
+        .or_else(|| file.text("instrument_model"))
+        .filter(|text| !text.is_empty())
+    {
+        meta.insert(INSTRUMENT.to_owned(), instrument);
+    }
+    if let Some(source) = text("external_file_ref_0") {
+        meta.insert(SOURCE.to_owned(), source);
+    }
+    meta
+}
+
+/// ISO date from an ANDI `YYYYMMDDhhmmss±hhmm` time stamp
+fn date(stamp: &str) -> Option<String> {
+    let digits = stamp.get(..8)?;
+    if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}",
+        &digits[..4],
+        &digits[4..6],
+        &digits[6..]
+    ))
+}
+
+fn time_units(units: &str) -> TimeUnits {
+    match &*units.to_lowercase() {
+        "minutes" | "minute" | "min" => TimeUnits::Minute,
+        "milliseconds" | "millisecond" | "ms" => TimeUnits::Millisecond,
+        _ => TimeUnits::Second,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::readers::netcdf::tests::{Values, Writer},
+        r#const::*,
+    };
+
+    fn writer(units: &str) -> Writer {
+        Writer::default()
+            .dimension("scan_number", 2)
+            .dimension("point_number", 5)
+            .attribute("sample_name", Values::text("Sample"))
+            .attribute(
+                "experiment_date_time_stamp",
+                Values::text("20240102030405+0000"),
+            )
+            .variable(
+                SCAN_ACQUISITION_TIME,
+                &[0],
+                vec![("units", Values::text(units))],
+                Values::doubles(&[1.0, 2.5]),
+            )
+            .variable(POINT_COUNT, &[0], Vec::new(), Values::ints(&[2, 3]))
+            .variable(
+                MASS_VALUES,
+                &[1],
+                Vec::new(),
+                Values::floats(&[50.0, 51.0, 50.0, 52.0, 53.0]),
+            )
+            .variable(
+                INTENSITY_VALUES,
+                &[1],
+                Vec::new(),
+                Values::floats(&[10.0, 20.0, 30.0, 40.0, 50.0]),
+            )
+    }
+
+    #[test]
+    fn scans() -> Result<()> {
+        let bytes = writer("seconds")
+            .variable(SCAN_INDEX, &[0], Vec::new(), Values::ints(&[0, 2]))
+            .write();
+        let frame = read(&bytes)?;
+        assert_eq!(frame.meta.get(NAME).map(String::as_str), Some("Sample"));
+        assert_eq!(frame.meta.get(DATE).map(String::as_str), Some("2024-01-02"));
+        let data_frame = &frame.data.data_frame;
+        let retention_time = data_frame[RETENTION_TIME].i32()?;
+        assert_eq!(
+            retention_time.into_no_null_iter().collect::<Vec<_>>(),
+            [1000, 1000, 2500, 2500, 2500],
+        );
+        let mass_to_charge = data_frame[MASS_TO_CHARGE].f32()?;
+        assert_eq!(
+            mass_to_charge.into_no_null_iter().collect::<Vec<_>>(),
+            [50.0, 51.0, 50.0, 52.0, 53.0],
+        );
+        let signal = data_frame[SIGNAL].u16()?;
+        assert_eq!(
+            signal.into_no_null_iter().collect::<Vec<_>>(),
+            [10, 20, 30, 40, 50],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn scan_index_from_point_count() -> Result<()> {
+        let frame = read(&writer("minutes").write())?;
+        let retention_time = frame.data.data_frame[RETENTION_TIME].i32()?;
+        assert_eq!(
+            retention_time.into_no_null_iter().collect::<Vec<_>>(),
+            [60_000, 60_000, 150_000, 150_000, 150_000],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn scan_exceeds_points() {
+        let bytes = writer("seconds")
+            .variable(SCAN_INDEX, &[0], Vec::new(), Values::ints(&[0, 3]))
+            .write();
+        assert!(read(&bytes).is_err());
+    }
+
+    #[test]
+    fn invalid_scan_index() {
+        for scan_index in [[0.0, -2.0], [0.0, f64::NAN], [0.0, 0.5], [0.0, f64::MAX]] {
+            let bytes = writer("seconds")
+                .variable(SCAN_INDEX, &[0], Vec::new(), Values::doubles(&scan_index))
+                .write();
+            assert!(read(&bytes).is_err());
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use super::{Builder, milliseconds};
+use crate::{app::states::settings::TimeUnits, utils::hash::HashedMetaDataFrame};
+use anyhow::{Result, bail};
+use metadata::Metadata;
+use polars::prelude::*;
+use std::{io::Cursor, iter::zip};
+
+/// Reads delimited text with the guessed options and column mapping
+pub(crate) fn read(name: &str, bytes: &[u8]) -> Result<HashedMetaDataFrame> {
+    let options = Options::guess(name, bytes);
+    let data_frame = options.parse(bytes)?;
+    Mapping::guess(&data_frame).convert(&data_frame, Metadata::default())
+}
+
+/// Largest retention time of a run guessed to be in minutes
+const MINUTES: f64 = 300.0;
+/// Largest retention time of a run guessed to be in seconds
+const SECONDS: f64 = 60.0 * MINUTES;
+
+/// Delimited text parse options
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Options {
+    pub(crate) separator: u8,
+    pub(crate) has_header: bool,
+    pub(crate) skip_rows: usize,
+    pub(crate) decimal_comma: bool,
+}
+
+impl Options {
+    /// Guesses the separator from the extension or the first line
+    pub(crate) fn guess(name: &str, bytes: &[u8]) -> Self {
+        let separator = if name.to_lowercase().ends_with(".tsv") {
+            b'\t'
+        } else {
+            let line = bytes
+                .split(|&byte| byte == b'\n')
+                .next()
+                .unwrap_or_default();
+            let count = |separator| line.iter().filter(|&&byte| byte == separator).count();
+            // The last of the most frequent wins, the space (also found in
+            // the column names) only when no other separator is as frequent
+            [b' ', b';', b'\t', b',']
+                .into_iter()
+                .max_by_key(|&separator| count(separator))
+                .unwrap_or(b',')
+        };
+        Self {
+            separator,
+            has_header: true,
+            skip_rows: 0,
+            decimal_comma: separator == b';',
+        }
+    }
+
+    pub(crate) fn parse(&self, bytes: &[u8]) -> PolarsResult<DataFrame> {
+        CsvReadOptions::default()
+            .with_has_header(self.has_header)
+            .with_skip_rows(self.skip_rows)
+            .with_parse_options(
+                CsvParseOptions::default()
+                    .with_separator(self.separator)
+                    .with_decimal_comma(self.decimal_comma),
+            )
+            .into_reader_with_file_handle(Cursor::new(bytes))
+            .finish()
+    }
+}
+
+/// Column mapping onto the long frame columns
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Mapping {
+    pub(crate) retention_time: Option<PlSmallStr>,
+    pub(crate) mass_to_charge: Option<PlSmallStr>,
+    pub(crate) signal: Option<PlSmallStr>,
+    pub(crate) units: TimeUnits,
+}
+
+impl Mapping {
+    /// Guesses the mapping from the whole words of the column names, falling
+    /// back to the unmapped columns in order
+    ///
+    /// The retention time units come from the column name, or from the range
+    /// of its values when the name has none.
+    pub(crate) fn guess(data_frame: &DataFrame) -> Self {
+        let names = data_frame
+            .get_column_names()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut taken = Vec::new();
+        let mut find = |words: &[&str], index: usize| {
+            let name = names
+                .iter()
+                .find(|name| !taken.contains(*name) && matches(name, words))
+                .or_else(|| names.iter().skip(index).find(|name| !taken.contains(*name)))
+                .cloned();
+            taken.extend(name.clone());
+            name
+        };
+        let retention_time = find(&["retention", "time", "rt"], 0);
+        let mass_to_charge = find(&["m/z", "mz", "mass"], 1);
+        let signal = find(
+            &[
+                "signal",
+                "intensity",
+                "abundance",
+                "area",
+                "height",
+                "counts",
+            ],
+            2,
+        );
+        let units = match &retention_time {
+            Some(name) if matches(name, &["ms", "msec", "millisecond", "milliseconds"]) => {
+                TimeUnits::Millisecond
+            }
+            Some(name) if matches(name, &["s", "sec", "second", "seconds"]) => TimeUnits::Second,
+            Some(name) if matches(name, &["min", "minute", "minutes"]) => TimeUnits::Minute,
+            Some(name) => {
+                let max = data_frame
+                    .column(name)
+                    .and_then(|column| column.cast(&DataType::Float64))
+                    .ok()
+                    .and_then(|column| column.f64().ok()?.max());
+                match max {
+                    Some(max) if max > SECONDS => TimeUnits::Millisecond,
+                    Some(max) if max > MINUTES => TimeUnits::Second,
+                    _ => TimeUnits::Minute,
+                }
+            }
+            None => TimeUnits::Minute,
+        };
+        Self {
+            retention_time,
+            mass_to_charge,
+            signal,
+            units,
+        }
+    }
+
+    /// Converts into the `i32` ms / `f32` / `u16` schema, rows with a null in
+    /// any mapped column are skipped
+    pub(crate) fn convert(
+        &self,
+        data_frame: &DataFrame,
+        meta: Metadata,
+    ) -> Result<HashedMetaDataFrame> {
+        let column = |name: &Option<PlSmallStr>, target| -> Result<Series> {
+            let Some(name) = name else {
+                bail!("{target} column is not mapped");
+            };
+            Ok(data_frame
+                .column(name)?
+                .as_materialized_series()
+                .cast(&DataType::Float64)?)
+        };
+        let retention_time = column(&self.retention_time, "Retention time")?;
+        let mass_to_charge = column(&self.mass_to_charge, "Mass to charge")?;
+        let signal = column(&self.signal, "Signal")?;
+        let mut builder = Builder::default();
+        for (retention_time, (mass_to_charge, signal)) in zip(
+            retention_time.f64()?,
+            zip(mass_to_charge.f64()?, signal.f64()?),
+        ) {
+            if let (Some(retention_time), Some(mass_to_charge), Some(signal)) =
+                (retention_time, mass_to_charge, signal)
+            {
+                builder.push(
+                    milliseconds(retention_time, self.units),
+                    mass_to_charge as _,
+                    signal,
+                );
+            }
+        }
+        builder.build(meta)
+    }
+}
+
+/// Whether a word of the column name is one of the words, a word with a slash
+/// (`m/z`) is looked for as is
+fn matches(name: &str, words: &[&str]) -> bool {
+    let name = name.to_lowercase();
+    words.iter().any(|word| {
+        if word.contains('/') {
+            name.contains(word)
+        } else {
+            name.split(|char: char| !char.is_alphanumeric())
+                .any(|token| token == *word)
+        }
+    })
+}
@@ -0,0 +1,220 @@
+use crate::{
+    app::{
+        computers::search::mass_spectrum,
+        data::{Data, IPC_MAGIC, MAGIC, PARQUET_MAGIC},
+        states::settings::TimeUnits,
+    },
+    r#const::*,
+    utils::hash::{HashedDataFrame, HashedMetaDataFrame},
+};
+use anyhow::{Result, bail};
+use metadata::{Metadata, NAME, polars::MetaDataFrame};
+use polars::prelude::*;
+use std::iter::zip;
+use tracing::{trace, warn};
+use uom::si::{
+    f64::Time,
+    time::{millisecond, minute, second},
+};
+
+pub(crate) const INSTRUMENT: &str = "Instrument";
+pub(crate) const SOURCE: &str = "Source";
+/// Factor the signal was scaled by to fit the `u16` range
+pub(crate) const SIGNAL_SCALE: &str = "SignalScale";
+
+/// Number of leading bytes inspected to sniff the file kind
+const SNIFF: usize = 1024;
+
+/// Reads a run, choosing the reader by content and file name
+pub(crate) fn read(name: &str, bytes: &[u8]) -> Result<HashedMetaDataFrame> {
+    let kind = Kind::new(name, bytes);
+    trace!(name, ?kind);
+    let mut frame = match kind {
+        Kind::Andi => andi::read(bytes)?,
+        Kind::Bin => Data::from_bin(bytes)?.frame,
+        Kind::Delimited => delimited::read(name, bytes)?,
+        Kind::Ipc => Data::from_ipc(bytes)?.frame,
+        Kind::Msp => bail!("{name} is a spectral library, not a run"),
+        Kind::MzMl => mzml::read(bytes)?,
+        Kind::Parquet => Data::from_parquet(bytes)?.frame,
+        Kind::Ron => ron(bytes)?,
+    };
+    frame
+        .meta
+        .entry(NAME.to_owned())
+        .or_insert_with(|| stem(name));
+    Ok(frame)
+}
+
+/// Reads a metadata container or a bare spectra frame, `[RETENTION_TIME,
+/// MASS_SPECTRUM]`, as written by [`Format::Ron`](crate::app::data::Format)
+fn ron(bytes: &[u8]) -> Result<HashedMetaDataFrame> {
+    let frame: MetaDataFrame = match ron::de::from_bytes(bytes) {
+        Ok(frame) => frame,
+        Err(error) => match ron::de::from_bytes::<DataFrame>(bytes) {
+            Ok(data_frame) if data_frame.schema().contains(MASS_SPECTRUM) => {
+                return spectra(&data_frame);
+            }
+            _ => return Err(error.into()),
+        },
+    };
+    let data = HashedDataFrame::new(frame.data)?;
+    Ok(MetaDataFrame::new(frame.meta, data))
+}
+
+/// Long frame from a spectra frame
+fn spectra(data_frame: &DataFrame) -> Result<HashedMetaDataFrame> {
+    let retention_time = data_frame[RETENTION_TIME].i32()?;
+    let mut builder = Builder::default();
+    for (index, retention_time) in retention_time.iter().enumerate() {
+        let Some(retention_time) = retention_time else {
+            continue;
+        };
+        let (mass_to_charge, signal): (Vec<_>, Vec<_>) =
+            mass_spectrum(data_frame, index)?.into_iter().unzip();
+        builder.scan(
+            retention_time,
+            mass_to_charge.into_iter().map(f64::from),
+            signal,
+        );
+    }
+    builder.build(Metadata::default())
+}
+
+/// File name without directories and extensions
+pub(crate) fn stem(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    name.split('.').next().unwrap_or(name).to_owned()
+}
+
+/// Input kind
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Kind {
+    Andi,
+    Bin,
+    Delimited,
+    Ipc,
+    Msp,
+    MzMl,
+    Parquet,
+    Ron,
+}
+
+impl Kind {
+    pub(crate) fn new(name: &str, bytes: &[u8]) -> Self {
+        if bytes.starts_with(MAGIC) {
+            return Self::Bin;
+        }
+        if bytes.starts_with(PARQUET_MAGIC) {
+            return Self::Parquet;
+        }
+        if bytes.starts_with(IPC_MAGIC) {
+            return Self::Ipc;
+        }
+        if netcdf::is_netcdf(bytes) {
+            return Self::Andi;
+        }
+        let head = &bytes[..bytes.len().min(SNIFF)];
+        if contains(head, b"<mzML") || contains(head, b"<indexedmzML") {
+            return Self::MzMl;
+        }
+        let name = name.to_lowercase();
+        if name.ends_with(".mzml") {
+            Self::MzMl
+        } else if name.ends_with(".cdf") {
+            Self::Andi
+        } else if name.ends_with(".parquet") {
+            Self::Parquet
+        } else if name.ends_with(".arrow") || name.ends_with(".ipc") {
+            Self::Ipc
+        } else if name.ends_with(".msp") {
+            Self::Msp
+        } else if name.ends_with(".csv") || name.ends_with(".tsv") || name.ends_with(".txt") {
+            Self::Delimited
+        } else {
+            Self::Ron
+        }
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+/// Long frame builder
+///
+/// Collects `RETENTION_TIME` (i32, ms), `MASS_TO_CHARGE` (f32) and `SIGNAL`
+/// (u16) points, the layout expected by the table computer.
+#[derive(Debug, Default)]
+pub(crate) struct Builder {
+    retention_time: Vec<i32>,
+    mass_to_charge: Vec<f32>,
+    signal: Vec<f64>,
+    maximum: f64,
+}
+
+impl Builder {
+    /// Push a scan
+    pub(crate) fn scan(
+        &mut self,
+        retention_time: i32,
+        mass_to_charge: impl IntoIterator<Item = f64>,
+        signal: impl IntoIterator<Item = f64>,
+    ) {
+        for (mass_to_charge, signal) in zip(mass_to_charge, signal) {
+            self.push(retention_time, mass_to_charge as _, signal);
+        }
+    }
+
+    /// Push a point
+    pub(crate) fn push(&mut self, retention_time: i32, mass_to_charge: f32, signal: f64) {
+        self.maximum = self.maximum.max(signal);
+        self.retention_time.push(retention_time);
+        self.mass_to_charge.push(mass_to_charge);
+        self.signal.push(signal);
+    }
+
+    /// Builds the frame
+    ///
+    /// A signal above the `u16` range is scaled down to fit, the factor is
+    /// recorded as [`SIGNAL_SCALE`] in the metadata instead of clipping the
+    /// peaks.
+    pub(crate) fn build(self, mut meta: Metadata) -> Result<HashedMetaDataFrame> {
+        let mut scale = 1.0;
+        if self.maximum > u16::MAX as f64 {
+            scale = u16::MAX as f64 / self.maximum;
+            let maximum = self.maximum;
+            warn!(maximum, scale, "Signal scaled to the u16 range");
+            meta.insert(SIGNAL_SCALE.to_owned(), scale.to_string());
+        }
+        let signal = self
+            .signal
+            .into_iter()
+            .map(|signal| (signal * scale).round() as u16)
+            .collect::<Vec<_>>();
+        let data_frame = df! {
+            RETENTION_TIME => self.retention_time,
+            MASS_TO_CHARGE => self.mass_to_charge,
+            SIGNAL => signal,
+        }?;
+        Ok(MetaDataFrame::new(meta, HashedDataFrame::new(data_frame)?))
+    }
+}
+
+/// Retention time in milliseconds
+pub(crate) fn milliseconds(value: f64, units: TimeUnits) -> i32 {
+    let time = match units {
+        TimeUnits::Millisecond => Time::new::<millisecond>(value),
+        TimeUnits::Second => Time::new::<second>(value),
+        TimeUnits::Minute => Time::new::<minute>(value),
+    };
+    time.get::<millisecond>().round() as _
+}
+
+pub(crate) mod andi;
+pub(crate) mod delimited;
+pub(crate) mod msp;
+pub(crate) mod mzml;
+pub(crate) mod netcdf;
@@ -0,0 +1,134 @@
+use crate::app::library::Entry;
+use anyhow::{Context as _, Result, bail};
+
+// https://chemdata.nist.gov/mass-spc/ms-search/docs/Ver20Man_11.pdf
+
+/// Reads a NIST MSP library
+pub(crate) fn read(bytes: &[u8]) -> Result<Vec<Entry>> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut entries = Vec::new();
+    let mut entry: Option<Entry> = None;
+    // Remaining peaks of the current entry
+    let mut peaks = 0usize;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if peaks > 0
+            && !line.is_empty()
+            && let Some(entry) = &mut entry
+        {
+            let tokens = peak_tokens(line);
+            for pair in tokens.chunks(2) {
+                let [mass_to_charge, signal] = pair else {
+                    bail!("MSP line {}: unpaired peak value", index + 1);
+                };
+                entry.peaks.push((
+                    mass_to_charge
+                        .parse()
+                        .with_context(|| format!("MSP line {}: mass to charge", index + 1))?,
+                    signal
+                        .parse()
+                        .with_context(|| format!("MSP line {}: signal", index + 1))?,
+                ));
+                peaks = peaks.saturating_sub(1);
+            }
+            continue;
+        }
+        peaks = 0;
+        if line.is_empty() {
+            entries.extend(entry.take());
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match &*key.trim().to_lowercase() {
+            "name" => {
+                entries.extend(entry.take());
+                entry = Some(Entry {
+                    name: value.to_owned(),
+                    ..Default::default()
+                });
+            }
+            key => {
+                let Some(entry) = &mut entry else {
+                    bail!("MSP line {}: {key} before name", index + 1);
+                };
+                match key {
+                    "formula" => entry.formula = Some(value.to_owned()),
+                    "mw" | "exactmass" => entry.molecular_weight = value.parse().ok(),
+                    "cas#" | "casno" => {
+                        let cas = value.split(';').next().unwrap_or(value).trim();
+                        entry.cas = Some(cas.to_owned());
+                    }
+                    "comment" | "comments" => entry.comment = Some(value.to_owned()),
+                    "num peaks" => {
+                        peaks = value
+                            .parse()
+                            .with_context(|| format!("MSP line {}: num peaks", index + 1))?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    entries.extend(entry);
+    if entries.is_empty() {
+        bail!("MSP contains no entries");
+    }
+    Ok(entries)
+}
+
+/// Peak values without annotations (`41 100; 43 20 "c3h7"`)
+fn peak_tokens(line: &str) -> Vec<&str> {
+    line.split('"')
+        .step_by(2)
+        .flat_map(|part| part.split([' ', '\t', ';', ',', ':', '(', ')']))
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries() -> Result<()> {
+        let text = r#"NAME: Propane
+Formula: C3H8
+MW: 44
+CAS#: 74-98-6; NIST#: 1
+Comments: "gas"
+Num Peaks: 3
+26 10; 29 "c2h5" 50
+44 999
+
+Name: Butane
+Num Peaks: 2
+43	999
+58	120
+"#;
+        let entries = read(text.as_bytes())?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "Propane");
+        assert_eq!(entries[0].formula.as_deref(), Some("C3H8"));
+        assert_eq!(entries[0].molecular_weight, Some(44.0));
+        assert_eq!(entries[0].cas.as_deref(), Some("74-98-6"));
+        assert_eq!(entries[0].comment.as_deref(), Some("\"gas\""));
+        assert_eq!(
+            entries[0].peaks,
+            [(26.0, 10.0), (29.0, 50.0), (44.0, 999.0)]
+        );
+        assert_eq!(entries[1].name, "Butane");
+        assert_eq!(entries[1].peaks, [(43.0, 999.0), (58.0, 120.0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert!(read(b"").is_err());
+        assert!(read(b"Formula: C3H8\nName: Propane\n").is_err());
+        assert!(read(b"Name: Propane\nNum Peaks: 2\n26 10; 29\n").is_err());
+        assert!(read(b"Name: Propane\nNum Peaks: 1\n26 x\n").is_err());
+    }
+}
@@ -0,0 +1,638 @@
+use super::{Builder, INSTRUMENT, SOURCE, milliseconds};
+use crate::{app::states::settings::TimeUnits, utils::hash::HashedMetaDataFrame};
+use anyhow::{Context as _, Result, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use flate2::read::ZlibDecoder;
+use metadata::{DATE, Metadata, NAME};
+use quick_xml::{
+    Reader,
+    events::{BytesStart, Event},
+};
+use std::{collections::HashMap, io::Read};
+use tracing::trace;
+
+// https://www.psidev.info/mzML
+// https://github.com/ms-numpress/ms-numpress
+
+// Spectrum
+const MS_LEVEL: &[u8] = b"MS:1000511";
+const SCAN_START_TIME: &[u8] = b"MS:1000016";
+// Binary data array
+const MASS_TO_CHARGE_ARRAY: &[u8] = b"MS:1000514";
+const INTENSITY_ARRAY: &[u8] = b"MS:1000515";
+const FLOAT_32: &[u8] = b"MS:1000521";
+const FLOAT_64: &[u8] = b"MS:1000523";
+const INTEGER_32: &[u8] = b"MS:1000519";
+const INTEGER_64: &[u8] = b"MS:1000522";
+const NO_COMPRESSION: &[u8] = b"MS:1000576";
+const ZLIB: &[u8] = b"MS:1000574";
+const NUMPRESS_LINEAR: &[u8] = b"MS:1002312";
+const NUMPRESS_PIC: &[u8] = b"MS:1002313";
+const NUMPRESS_SLOF: &[u8] = b"MS:1002314";
+const NUMPRESS_LINEAR_ZLIB: &[u8] = b"MS:1002746";
+const NUMPRESS_PIC_ZLIB: &[u8] = b"MS:1002747";
+const NUMPRESS_SLOF_ZLIB: &[u8] = b"MS:1002748";
+// Units
+const MILLISECOND: &[u8] = b"UO:0000028";
+const SECOND: &[u8] = b"UO:0000010";
+const MINUTE: &[u8] = b"UO:0000031";
+
+/// Reads an mzML (indexed or not) document into a long frame
+///
+/// Only MS1 spectra are taken, the index of an indexed document is ignored.
+/// Referenceable param groups are applied where they are referenced.
+pub(crate) fn read(bytes: &[u8]) -> Result<HashedMetaDataFrame> {
+    let mut parser = Parser::default();
+    let mut reader = Reader::from_reader(bytes);
+    loop {
+        let position = reader.buffer_position();
+        match reader
+            .read_event()
+            .with_context(|| format!("mzML at byte {position}"))?
+        {
+            Event::Start(start) => parser.start(&start)?,
+            Event::Empty(start) => {
+                parser.start(&start)?;
+                parser.end(start.local_name().as_ref())?;
+            }
+            Event::End(end) => parser.end(end.local_name().as_ref())?,
+            Event::Text(text) => {
+                if let Some(array) = &mut parser.array {
+                    array.binary.extend_from_slice(&text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    trace!(spectrums = parser.spectrums);
+    if parser.spectrums == 0 {
+        bail!("mzML contains no MS1 spectra");
+    }
+    parser.builder.build(parser.meta)
+}
+
+/// Parser
+#[derive(Default)]
+struct Parser {
+    builder: Builder,
+    meta: Metadata,
+    path: Vec<Vec<u8>>,
+    spectrum: Option<Spectrum>,
+    array: Option<Array>,
+    spectrums: usize,
+    /// Referenceable param groups by id
+    groups: HashMap<String, Vec<BytesStart<'static>>>,
+    /// Param group being read
+    group: Option<(String, Vec<BytesStart<'static>>)>,
+}
+
+impl Parser {
+    fn start(&mut self, start: &BytesStart) -> Result<()> {
+        let name = start.local_name().as_ref().to_vec();
+        match &*name {
+            b"run" => {
+                if let Some(id) = attribute(start, b"id")? {
+                    self.meta.entry(NAME.to_owned()).or_insert(id);
+                }
+                if let Some(timestamp) = attribute(start, b"startTimeStamp")? {
+                    let date = timestamp.split('T').next().unwrap_or(&timestamp);
+                    self.meta.insert(DATE.to_owned(), date.to_owned());
+                }
+            }
+            b"sample" => {
+                if let Some(sample) = attribute(start, b"name")? {
+                    self.meta.insert(NAME.to_owned(), sample);
+                }
+            }
+            b"sourceFile" => {
+                if let Some(source) = attribute(start, b"name")? {
+                    self.meta.entry(SOURCE.to_owned()).or_insert(source);
+                }
+            }
+            b"spectrum" => self.spectrum = Some(Spectrum::default()),
+            b"binaryDataArray" if self.spectrum.is_some() => self.array = Some(Array::default()),
+            b"referenceableParamGroup" => {
+                let id = attribute(start, b"id")?.unwrap_or_default();
+                self.group = Some((id, Vec::new()));
+            }
+            b"referenceableParamGroupRef" => {
+                let Some(id) = attribute(start, b"ref")? else {
+                    bail!("mzML param group reference without ref");
+                };
+                let Some(parameters) = self.groups.get(&id).cloned() else {
+                    bail!("mzML param group {id} is not defined");
+                };
+                for parameter in &parameters {
+                    self.parameter(parameter)?;
+                }
+            }
+            b"cvParam" => match &mut self.group {
+                Some((_, parameters)) => parameters.push(start.clone().into_owned()),
+                None => self.parameter(start)?,
+            },
+            _ => {}
+        }
+        self.path.push(name);
+        Ok(())
+    }
+
+    fn end(&mut self, name: &[u8]) -> Result<()> {
+        self.path.pop();
+        match name {
+            b"referenceableParamGroup" => {
+                if let Some((id, parameters)) = self.group.take() {
+                    self.groups.insert(id, parameters);
+                }
+            }
+            b"binaryDataArray" => {
+                if let Some(array) = self.array.take()
+                    && let Some(spectrum) = &mut self.spectrum
+                {
+                    let kind = array.kind;
+                    let values = array.decode()?;
+                    match kind {
+                        Some(Kind::MassToCharge) => spectrum.mass_to_charge = values,
+                        Some(Kind::Intensity) => spectrum.intensity = values,
+                        None => {}
+                    }
+                }
+            }
+            b"spectrum" => {
+                if let Some(spectrum) = self.spectrum.take()
+                    && spectrum.ms_level.is_none_or(|ms_level| ms_level == 1)
+                {
+                    let Some(retention_time) = spectrum.retention_time else {
+                        bail!("mzML spectrum {} has no scan start time", self.spectrums);
+                    };
+                    if spectrum.mass_to_charge.len() != spectrum.intensity.len() {
+                        bail!(
+                            "mzML spectrum {} array lengths differ: {} m/z, {} intensities",
+                            self.spectrums,
+                            spectrum.mass_to_charge.len(),
+                            spectrum.intensity.len(),
+                        );
+                    }
+                    self.builder
+                        .scan(retention_time, spectrum.mass_to_charge, spectrum.intensity);
+                    self.spectrums += 1;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn parameter(&mut self, start: &BytesStart) -> Result<()> {
+        let Some(accession) = start.try_get_attribute(b"accession")? else {
+            return Ok(());
+        };
+        let accession = &*accession.value;
+        let parent = self.path.last().map(Vec::as_slice);
+        if let Some(array) = &mut self.array {
+            match accession {
+                MASS_TO_CHARGE_ARRAY => array.kind = Some(Kind::MassToCharge),
+                INTENSITY_ARRAY => array.kind = Some(Kind::Intensity),
+                FLOAT_32 => array.precision = Precision::Float32,
+                FLOAT_64 => array.precision = Precision::Float64,
+                INTEGER_32 => array.precision = Precision::Integer32,
+                INTEGER_64 => array.precision = Precision::Integer64,
+                NO_COMPRESSION => {}
+                ZLIB => array.zlib = true,
+                NUMPRESS_LINEAR => array.numpress = Some(Numpress::Linear),
+                NUMPRESS_PIC => array.numpress = Some(Numpress::Pic),
+                NUMPRESS_SLOF => array.numpress = Some(Numpress::Slof),
+                NUMPRESS_LINEAR_ZLIB => {
+                    array.numpress = Some(Numpress::Linear);
+                    array.zlib = true;
+                }
+                NUMPRESS_PIC_ZLIB => {
+                    array.numpress = Some(Numpress::Pic);
+                    array.zlib = true;
+                }
+                NUMPRESS_SLOF_ZLIB => {
+                    array.numpress = Some(Numpress::Slof);
+                    array.zlib = true;
+                }
+                _ => {}
+            }
+        } else if let Some(spectrum) = &mut self.spectrum {
+            match accession {
+                MS_LEVEL => {
+                    let value = attribute(start, b"value")?.unwrap_or_default();
+                    spectrum.ms_level = Some(value.parse().context("mzML ms level")?);
+                }
+                SCAN_START_TIME => {
+                    let value = attribute(start, b"value")?.unwrap_or_default();
+                    let value = value.parse().context("mzML scan start time")?;
+                    let units = match start.try_get_attribute(b"unitAccession")? {
+                        Some(unit) if &*unit.value == MILLISECOND => TimeUnits::Millisecond,
+                        Some(unit) if &*unit.value == MINUTE => TimeUnits::Minute,
+                        Some(unit) if &*unit.value == SECOND => TimeUnits::Second,
+                        Some(unit) => bail!(
+                            "mzML unsupported scan start time unit {}",
+                            String::from_utf8_lossy(&unit.value),
+                        ),
+                        None => TimeUnits::Second,
+                    };
+                    spectrum.retention_time = Some(milliseconds(value, units));
+                }
+                _ => {}
+            }
+        } else if parent == Some(b"instrumentConfiguration")
+            && let Some(instrument) = attribute(start, b"name")?
+        {
+            self.meta.entry(INSTRUMENT.to_owned()).or_insert(instrument);
+        }
+        Ok(())
+    }
+}
+
+fn attribute(start: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+    Ok(match start.try_get_attribute(name)? {
+        Some(attribute) => Some(attribute.unescape_value()?.into_owned()),
+        None => None,
+    })
+}
+
+/// Spectrum
+#[derive(Default)]
+struct Spectrum {
+    ms_level: Option<u8>,
+    retention_time: Option<i32>,
+    mass_to_charge: Vec<f64>,
+    intensity: Vec<f64>,
+}
+
+/// Binary data array
+#[derive(Default)]
+struct Array {
+    kind: Option<Kind>,
+    precision: Precision,
+    zlib: bool,
+    numpress: Option<Numpress>,
+    binary: Vec<u8>,
+}
+
+impl Array {
+    fn decode(self) -> Result<Vec<f64>> {
+        let mut encoded = self.binary;
+        encoded.retain(|byte| !byte.is_ascii_whitespace());
+        let mut bytes = STANDARD.decode(encoded).context("mzML binary base64")?;
+        if self.zlib {
+            let mut decompressed = Vec::new();
+            ZlibDecoder::new(&*bytes)
+                .read_to_end(&mut decompressed)
+                .context("mzML binary zlib")?;
+            bytes = decompressed;
+        }
+        if let Some(numpress) = self.numpress {
+            return numpress.decode(&bytes);
+        }
+        let size = self.precision.size();
+        if bytes.len() % size != 0 {
+            bail!(
+                "mzML binary length {} is not a multiple of {size}",
+                bytes.len()
+            );
+        }
+        Ok(bytes
+            .chunks_exact(size)
+            .map(|chunk| match self.precision {
+                Precision::Float32 => f32::from_le_bytes(chunk.try_into().unwrap()) as _,
+                Precision::Float64 => f64::from_le_bytes(chunk.try_into().unwrap()),
+                Precision::Integer32 => i32::from_le_bytes(chunk.try_into().unwrap()) as _,
+                Precision::Integer64 => i64::from_le_bytes(chunk.try_into().unwrap()) as _,
+            })
+            .collect())
+    }
+}
+
+/// Binary data array kind
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    MassToCharge,
+    Intensity,
+}
+
+/// Binary data array precision
+#[derive(Clone, Copy, Debug, Default)]
+enum Precision {
+    Float32,
+    #[default]
+    Float64,
+    Integer32,
+    Integer64,
+}
+
+impl Precision {
+    const fn size(self) -> usize {
+        match self {
+            Self::Float32 | Self::Integer32 => 4,
+            Self::Float64 | Self::Integer64 => 8,
+        }
+    }
+}
+
+/// MS-Numpress compression
+#[derive(Clone, Copy, Debug)]
+enum Numpress {
+    Linear,
+    Pic,
+    Slof,
+}
+
+impl Numpress {
+    fn decode(self, bytes: &[u8]) -> Result<Vec<f64>> {
+        match self {
+            Self::Linear => linear(bytes),
+            Self::Pic => Ok(pic(bytes)),
+            Self::Slof => slof(bytes),
+        }
+    }
+}
+
+fn fixed_point(bytes: &[u8]) -> Result<f64> {
+    let Some(bytes) = bytes.get(..8) else {
+        bail!("numpress data is shorter than the fixed point");
+    };
+    Ok(f64::from_be_bytes(bytes.try_into()?))
+}
+
+fn linear(bytes: &[u8]) -> Result<Vec<f64>> {
+    let fixed_point = fixed_point(bytes)?;
+    let mut values = Vec::new();
+    if bytes.len() == 8 {
+        return Ok(values);
+    }
+    let Some(first) = bytes.get(8..12) else {
+        bail!("numpress linear data is corrupt");
+    };
+    let mut ints = [0, u32::from_le_bytes(first.try_into()?), 0];
+    values.push(ints[1] as f64 / fixed_point);
+    if bytes.len() == 12 {
+        return Ok(values);
+    }
+    let Some(second) = bytes.get(12..16) else {
+        bail!("numpress linear data is corrupt");
+    };
+    ints[2] = u32::from_le_bytes(second.try_into()?);
+    values.push(ints[2] as f64 / fixed_point);
+    let mut halfs = Halfs::new(&bytes[16..]);
+    while let Some(int) = halfs.next_int() {
+        ints[0] = ints[1];
+        ints[1] = ints[2];
+        let extrapolated = ints[1].wrapping_add(ints[1].wrapping_sub(ints[0])) as i64;
+        let value = extrapolated + int as i32 as i64;
+        values.push(value as f64 / fixed_point);
+        ints[2] = value as u32;
+    }
+    Ok(values)
+}
+
+fn pic(bytes: &[u8]) -> Vec<f64> {
+    let mut halfs = Halfs::new(bytes);
+    let mut values = Vec::new();
+    while let Some(int) = halfs.next_int() {
+        values.push(int as f64);
+    }
+    values
+}
+
+fn slof(bytes: &[u8]) -> Result<Vec<f64>> {
+    let fixed_point = fixed_point(bytes)?;
+    Ok(bytes[8..]
+        .chunks_exact(2)
+        .map(|chunk| (u16::from_le_bytes([chunk[0], chunk[1]]) as f64 / fixed_point).exp() - 1.0)
+        .collect())
+}
+
+/// Numpress half byte reader
+struct Halfs<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl<'a> Halfs<'a> {
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, index: 0 }
+    }
+
+    fn next_half(&mut self) -> Option<u32> {
+        let byte = self.bytes.get(self.index / 2)?;
+        let half = if self.index % 2 == 0 {
+            byte >> 4
+        } else {
+            byte & 0xf
+        };
+        self.index += 1;
+        Some(half as _)
+    }
+
+    /// Reads a truncated integer, `None` at the end (a zero padding half
+    /// byte is the terminator).
+    fn next_int(&mut self) -> Option<u32> {
+        let remaining = self.bytes.len() * 2 - self.index;
+        if remaining == 0 || remaining == 1 && self.bytes[self.index / 2] & 0xf == 0 {
+            return None;
+        }
+        let head = self.next_half()?;
+        let (count, mut int) = if head <= 8 {
+            (head, 0)
+        } else {
+            let count = head - 8;
+            let int = (0..count).fold(0, |int, index| int | 0xf000_0000 >> (4 * index));
+            (count, int)
+        };
+        for index in 0..8 - count {
+            int |= self.next_half()? << (4 * index);
+        }
+        Some(int)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#const::*;
+    use std::iter::{from_fn, zip};
+
+    /// Numpress truncated integer, as half bytes
+    fn encode_int(int: u32, halfs: &mut Vec<u8>) {
+        let nibble = |index: usize| ((int >> (4 * (7 - index))) & 0xf) as u8;
+        let (head, count) = match nibble(0) {
+            0 => {
+                let count = (0..8).take_while(|&index| nibble(index) == 0).count();
+                (count as u8, count)
+            }
+            0xf => {
+                let count = (0..7).take_while(|&index| nibble(index) == 0xf).count();
+                (count as u8 + 8, count)
+            }
+            _ => (0, 0),
+        };
+        halfs.push(head);
+        halfs.extend((0..8 - count).map(|index| ((int >> (4 * index)) & 0xf) as u8));
+    }
+
+    /// Packs half bytes, an odd count is padded by a zero half byte
+    fn pack(halfs: &[u8]) -> Vec<u8> {
+        halfs
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0))
+            .collect()
+    }
+
+    fn assert_close(left: &[f64], right: &[f64], tolerance: f64) {
+        assert_eq!(left.len(), right.len(), "{left:?} != {right:?}");
+        for (left, right) in zip(left, right) {
+            assert!((left - right).abs() <= tolerance, "{left} != {right}");
+        }
+    }
+
+    #[test]
+    fn halfs() {
+        let mut halfs = Vec::new();
+        for int in [0, 5, 0x1234, u32::MAX, -2i32 as u32, 0x8000_0000] {
+            encode_int(int, &mut halfs);
+        }
+        let bytes = pack(&halfs);
+        let mut halfs = Halfs::new(&bytes);
+        let ints = from_fn(|| halfs.next_int()).collect::<Vec<_>>();
+        assert_eq!(ints, [0, 5, 0x1234, u32::MAX, -2i32 as u32, 0x8000_0000]);
+    }
+
+    #[test]
+    fn numpress_linear() -> Result<()> {
+        let fixed_point = 1000.0;
+        let expected = [100.0, 100.5, 101.25, 103.0, 150.125, 149.875, 0.5];
+        let ints = expected
+            .iter()
+            .map(|value| (value * fixed_point) as i64)
+            .collect::<Vec<_>>();
+        let mut bytes = f64::to_be_bytes(fixed_point).to_vec();
+        bytes.extend((ints[0] as u32).to_le_bytes());
+        bytes.extend((ints[1] as u32).to_le_bytes());
+        let mut halfs = Vec::new();
+        for index in 2..ints.len() {
+            let extrapolated = 2 * ints[index - 1] - ints[index - 2];
+            encode_int((ints[index] - extrapolated) as i32 as u32, &mut halfs);
+        }
+        bytes.extend(pack(&halfs));
+        assert_close(&linear(&bytes)?, &expected, 1e-9);
+        // Fewer than three values
+        assert_close(&linear(&bytes[..12])?, &expected[..1], 1e-9);
+        assert_close(&linear(&bytes[..16])?, &expected[..2], 1e-9);
+        assert!(linear(&bytes[..4]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn numpress_pic() {
+        let expected = [0.0, 1.0, 15.0, 16.0, 255.0, 4096.0, 65535.0];
+        let mut halfs = Vec::new();
+        for value in expected {
+            encode_int(value as u32, &mut halfs);
+        }
+        assert_close(&pic(&pack(&halfs)), &expected, 0.0);
+    }
+
+    #[test]
+    fn numpress_slof() -> Result<()> {
+        let fixed_point = 4000.0;
+        let expected = [0.0, 1.0, 10.0, 1000.0, 12345.0];
+        let mut bytes = f64::to_be_bytes(fixed_point).to_vec();
+        for value in expected {
+            let int = (f64::ln_1p(value) * fixed_point).round() as u16;
+            bytes.extend(int.to_le_bytes());
+        }
+        let values = slof(&bytes)?;
+        assert_eq!(values.len(), expected.len());
+        for (value, expected) in zip(values, expected) {
+            // Half a fixed point step of the logarithm
+            let tolerance = (expected + 1.0) * (0.5 / fixed_point).exp_m1();
+            assert!(
+                (value - expected).abs() <= tolerance,
+                "{value} != {expected}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn param_group() -> Result<()> {
+        let encode = |bytes: Vec<u8>| STANDARD.encode(bytes);
+        let mass_to_charge = encode(
+            [50.5f64, 51.0]
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+        );
+        let intensity = encode(
+            [100f32, 200.0]
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+        );
+        let document = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<mzML>
+  <referenceableParamGroupList count="1">
+    <referenceableParamGroup id="mz">
+      <cvParam accession="MS:1000514" name="m/z array"/>
+      <cvParam accession="MS:1000523" name="64-bit float"/>
+    </referenceableParamGroup>
+  </referenceableParamGroupList>
+  <run id="run">
+    <spectrumList count="1">
+      <spectrum index="0" id="scan=1">
+        <cvParam accession="MS:1000511" name="ms level" value="1"/>
+        <scanList count="1">
+          <scan>
+            <cvParam accession="MS:1000016" value="1.5" unitAccession="UO:0000031"/>
+          </scan>
+        </scanList>
+        <binaryDataArrayList count="2">
+          <binaryDataArray>
+            <referenceableParamGroupRef ref="mz"/>
+            <binary>{mass_to_charge}</binary>
+          </binaryDataArray>
+          <binaryDataArray>
+            <cvParam accession="MS:1000515" name="intensity array"/>
+            <cvParam accession="MS:1000521" name="32-bit float"/>
+            <binary>{intensity}</binary>
+          </binaryDataArray>
+        </binaryDataArrayList>
+      </spectrum>
+    </spectrumList>
+  </run>
+</mzML>"#
+        );
+        let frame = read(document.as_bytes())?;
+        assert_eq!(frame.meta.get(NAME).map(String::as_str), Some("run"));
+        let data_frame = &frame.data.data_frame;
+        let retention_time = data_frame[RETENTION_TIME].i32()?;
+        assert_eq!(
+            retention_time.into_no_null_iter().collect::<Vec<_>>(),
+            [90_000; 2]
+        );
+        let mass_to_charge = data_frame[MASS_TO_CHARGE].f32()?;
+        assert_eq!(
+            mass_to_charge.into_no_null_iter().collect::<Vec<_>>(),
+            [50.5, 51.0]
+        );
+        let signal = data_frame[SIGNAL].u16()?;
+        assert_eq!(signal.into_no_null_iter().collect::<Vec<_>>(), [100, 200]);
+        Ok(())
+    }
+
+    #[test]
+    fn undefined_param_group() {
+        let document = br#"<mzML><run><spectrumList><spectrum>
+            <binaryDataArrayList><binaryDataArray>
+            <referenceableParamGroupRef ref="missing"/>
+            </binaryDataArray></binaryDataArrayList>
+            </spectrum></spectrumList></run></mzML>"#;
+        assert!(read(document).is_err());
+    }
+}
@@ -0,0 +1,784 @@
+use anyhow::{Context as _, Result, bail, ensure};
+use std::{borrow::Cow, ops::Range};
+
+// https://docs.unidata.ucar.edu/netcdf-c/current/file_format_specifications.html
+
+const MAGIC: &[u8] = b"CDF";
+const ABSENT: u32 = 0x00;
+const NC_DIMENSION: u32 = 0x0A;
+const NC_VARIABLE: u32 = 0x0B;
+const NC_ATTRIBUTE: u32 = 0x0C;
+const STREAMING: u32 = 0xFFFF_FFFF;
+const OVERFLOW: &str = "netCDF header sizes overflow";
+
+/// Whether the bytes start with a netCDF classic or 64-bit offset header
+pub(crate) fn is_netcdf(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC) && matches!(bytes.get(3), Some(1 | 2))
+}
+
+/// netCDF-3 classic (CDF-1) and 64-bit offset (CDF-2) file
+#[derive(Debug)]
+pub(crate) struct File<'a> {
+    bytes: &'a [u8],
+    records: usize,
+    dimensions: Vec<Dimension>,
+    attributes: Vec<Attribute>,
+    variables: Vec<Variable>,
+}
+
+impl<'a> File<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Result<Self> {
+        ensure!(is_netcdf(bytes), "not a netCDF classic file");
+        let mut header = Header {
+            bytes,
+            position: 4,
+            offset: if bytes[3] == 1 { 4 } else { 8 },
+        };
+        let records = header.u32()?;
+        let dimensions = header.dimensions()?;
+        let attributes = header.attributes()?;
+        let variables = header.variables()?;
+        for variable in &variables {
+            ensure!(
+                variable
+                    .dimensions
+                    .iter()
+                    .all(|&index| index < dimensions.len()),
+                "netCDF variable {} refers to a missing dimension",
+                variable.name,
+            );
+        }
+        let mut file = Self {
+            bytes,
+            records: records as _,
+            dimensions,
+            attributes,
+            variables,
+        };
+        if records == STREAMING {
+            file.records = file.streaming_records()?;
+        }
+        Ok(file)
+    }
+
+    /// Global attribute
+    pub(crate) fn attribute(&self, name: &str) -> Option<&Value> {
+        find(&self.attributes, name)
+    }
+
+    pub(crate) fn variable(&self, name: &str) -> Option<&Variable> {
+        self.variables.iter().find(|variable| variable.name == name)
+    }
+
+    /// Variable values, converted to `f64` and multiplied by its
+    /// `scale_factor` attribute
+    pub(crate) fn values(&self, name: &str) -> Result<Vec<f64>> {
+        let variable = self
+            .variable(name)
+            .with_context(|| format!("netCDF variable {name} is missing"))?;
+        // Every range is checked against the file before allocating
+        let ranges = self.ranges(variable)?;
+        let size = variable.r#type.size();
+        let mut values = Vec::with_capacity(ranges.iter().map(|range| range.len() / size).sum());
+        for range in ranges {
+            values.extend(variable.r#type.values(&self.bytes[range]));
+        }
+        if let Some(scale_factor) = variable.attribute("scale_factor").and_then(Value::first) {
+            for value in &mut values {
+                *value *= scale_factor;
+            }
+        }
+        Ok(values)
+    }
+
+    /// Character variable as text with trailing nulls removed
+    pub(crate) fn text(&self, name: &str) -> Option<String> {
+        let variable = self.variable(name)?;
+        if variable.r#type != Type::Char {
+            return None;
+        }
+        let mut text = Vec::new();
+        for range in self.ranges(variable).ok()? {
+            text.extend_from_slice(&self.bytes[range]);
+        }
+        Some(trim(&text).into_owned())
+    }
+
+    fn is_record(&self, variable: &Variable) -> bool {
+        variable
+            .dimensions
+            .first()
+            .is_some_and(|&index| self.dimensions[index].length == 0)
+    }
+
+    /// Number of values of a variable
+    fn len(&self, variable: &Variable) -> Result<usize> {
+        variable.dimensions.iter().try_fold(1usize, |len, &index| {
+            let length = match self.dimensions[index].length {
+                0 => self.records,
+                length => length,
+            };
+            len.checked_mul(length).context(OVERFLOW)
+        })
+    }
+
+    /// Byte length of one record of a record variable, or of a whole
+    /// non-record variable
+    fn length(&self, variable: &Variable) -> Result<usize> {
+        let dimensions = if self.is_record(variable) {
+            &variable.dimensions[1..]
+        } else {
+            &variable.dimensions[..]
+        };
+        dimensions
+            .iter()
+            .try_fold(variable.r#type.size(), |len, &index| {
+                len.checked_mul(self.dimensions[index].length)
+                    .context(OVERFLOW)
+            })
+    }
+
+    /// Byte ranges of a variable data, all inside the file
+    fn ranges(&self, variable: &Variable) -> Result<Vec<Range<usize>>> {
+        let range = |start: usize, length: usize| -> Result<Range<usize>> {
+            let end = start.checked_add(length).context(OVERFLOW)?;
+            ensure!(
+                end <= self.bytes.len(),
+                "netCDF variable {} is truncated",
+                variable.name,
+            );
+            Ok(start..end)
+        };
+        if !self.is_record(variable) {
+            let length = self
+                .len(variable)?
+                .checked_mul(variable.r#type.size())
+                .context(OVERFLOW)?;
+            return Ok(vec![range(variable.begin, length)?]);
+        }
+        let length = self.length(variable)?;
+        if self.records == 0 || length == 0 {
+            return Ok(Vec::new());
+        }
+        let record_size = self.record_size()?;
+        // A record holds every record variable, so the records fit in the file
+        ensure!(
+            record_size >= length,
+            "netCDF record size {record_size} is smaller than variable {}",
+            variable.name,
+        );
+        let last = record_size
+            .checked_mul(self.records - 1)
+            .and_then(|offset| variable.begin.checked_add(offset))
+            .context(OVERFLOW)?;
+        range(last, length)?;
+        (0..self.records)
+            .map(|record| range(variable.begin + record * record_size, length))
+            .collect()
+    }
+
+    /// Size of one record across all record variables
+    fn record_size(&self) -> Result<usize> {
+        let records = self
+            .variables
+            .iter()
+            .filter(|variable| self.is_record(variable))
+            .collect::<Vec<_>>();
+        // A single record variable is not padded
+        if let [variable] = records[..] {
+            return self.length(variable);
+        }
+        records.iter().try_fold(0usize, |size, variable| {
+            size.checked_add(variable.size).context(OVERFLOW)
+        })
+    }
+
+    fn streaming_records(&self) -> Result<usize> {
+        let record_size = self.record_size()?;
+        let Some(begin) = self
+            .variables
+            .iter()
+            .filter(|variable| self.is_record(variable))
+            .map(|variable| variable.begin)
+            .min()
+        else {
+            return Ok(0);
+        };
+        if record_size == 0 {
+            return Ok(0);
+        }
+        Ok(self.bytes.len().saturating_sub(begin) / record_size)
+    }
+}
+
+/// Dimension
+#[derive(Debug)]
+struct Dimension {
+    /// Zero for the record (unlimited) dimension
+    length: usize,
+}
+
+/// Attribute
+#[derive(Debug)]
+struct Attribute {
+    name: String,
+    value: Value,
+}
+
+/// Attribute value
+#[derive(Debug)]
+pub(crate) enum Value {
+    Text(String),
+    Numbers(Vec<f64>),
+}
+
+impl Value {
+    pub(crate) fn text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => Some(text),
+            Self::Numbers(_) => None,
+        }
+    }
+
+    pub(crate) fn first(&self) -> Option<f64> {
+        match self {
+            Self::Text(_) => None,
+            Self::Numbers(numbers) => numbers.first().copied(),
+        }
+    }
+}
+
+/// Variable
+#[derive(Debug)]
+pub(crate) struct Variable {
+    name: String,
+    dimensions: Vec<usize>,
+    attributes: Vec<Attribute>,
+    r#type: Type,
+    size: usize,
+    begin: usize,
+}
+
+impl Variable {
+    pub(crate) fn attribute(&self, name: &str) -> Option<&Value> {
+        find(&self.attributes, name)
+    }
+}
+
+fn find<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a Value> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name == name)
+        .map(|attribute| &attribute.value)
+}
+
+/// External type
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Type {
+    Byte,
+    Char,
+    Short,
+    Int,
+    Float,
+    Double,
+}
+
+impl Type {
+    fn new(value: u32) -> Result<Self> {
+        Ok(match value {
+            1 => Self::Byte,
+            2 => Self::Char,
+            3 => Self::Short,
+            4 => Self::Int,
+            5 => Self::Float,
+            6 => Self::Double,
+            _ => bail!("netCDF type {value} is not supported"),
+        })
+    }
+
+    const fn size(self) -> usize {
+        match self {
+            Self::Byte | Self::Char => 1,
+            Self::Short => 2,
+            Self::Int | Self::Float => 4,
+            Self::Double => 8,
+        }
+    }
+
+    fn values(self, bytes: &[u8]) -> impl Iterator<Item = f64> {
+        bytes
+            .chunks_exact(self.size())
+            .map(move |chunk| match self {
+                Self::Byte => chunk[0] as i8 as _,
+                Self::Char => chunk[0] as _,
+                Self::Short => i16::from_be_bytes([chunk[0], chunk[1]]) as _,
+                Self::Int => i32::from_be_bytes(chunk.try_into().unwrap()) as _,
+                Self::Float => f32::from_be_bytes(chunk.try_into().unwrap()) as _,
+                Self::Double => f64::from_be_bytes(chunk.try_into().unwrap()),
+            })
+    }
+}
+
+/// Header cursor
+struct Header<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    /// Size of variable offsets (4 for CDF-1, 8 for CDF-2)
+    offset: usize,
+}
+
+impl<'a> Header<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let Some(bytes) = self
+            .position
+            .checked_add(length)
+            .and_then(|end| self.bytes.get(self.position..end))
+        else {
+            bail!("netCDF header is truncated at byte {}", self.position);
+        };
+        self.position += length;
+        Ok(bytes)
+    }
+
+    /// Takes `length` bytes and skips the padding to a 4 byte boundary
+    fn padded(&mut self, length: usize) -> Result<&'a [u8]> {
+        let bytes = self.take(length)?;
+        self.take(length.next_multiple_of(4) - length)?;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn offset(&mut self) -> Result<usize> {
+        Ok(match self.offset {
+            4 => self.u32()? as _,
+            _ => u64::from_be_bytes(self.take(8)?.try_into()?) as _,
+        })
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let length = self.u32()? as _;
+        Ok(String::from_utf8_lossy(self.padded(length)?).into_owned())
+    }
+
+    /// List tag and number of elements
+    fn list(&mut self, tag: u32) -> Result<usize> {
+        let actual = self.u32()?;
+        let count = self.u32()? as _;
+        ensure!(
+            actual == tag || actual == ABSENT && count == 0,
+            "netCDF header list tag {actual:#x}, expected {tag:#x}",
+        );
+        Ok(count)
+    }
+
+    fn dimensions(&mut self) -> Result<Vec<Dimension>> {
+        let count = self.list(NC_DIMENSION)?;
+        (0..count)
+            .map(|_| {
+                self.name()?;
+                Ok(Dimension {
+                    length: self.u32()? as _,
+                })
+            })
+            .collect()
+    }
+
+    fn attributes(&mut self) -> Result<Vec<Attribute>> {
+        let count = self.list(NC_ATTRIBUTE)?;
+        (0..count)
+            .map(|_| {
+                let name = self.name()?;
+                let r#type = Type::new(self.u32()?)?;
+                let count = self.u32()? as usize;
+                let length = count.checked_mul(r#type.size()).context(OVERFLOW)?;
+                let bytes = self.padded(length)?;
+                let value = match r#type {
+                    Type::Char => Value::Text(trim(bytes).into_owned()),
+                    _ => Value::Numbers(r#type.values(bytes).collect()),
+                };
+                Ok(Attribute { name, value })
+            })
+            .collect()
+    }
+
+    fn variables(&mut self) -> Result<Vec<Variable>> {
+        let count = self.list(NC_VARIABLE)?;
+        (0..count)
+            .map(|_| {
+                let name = self.name()?;
+                let count = self.u32()?;
+                let dimensions = (0..count)
+                    .map(|_| Ok(self.u32()? as _))
+                    .collect::<Result<_>>()?;
+                let attributes = self.attributes()?;
+                let r#type = Type::new(self.u32()?)?;
+                let size = self.u32()? as _;
+                let begin = self.offset()?;
+                Ok(Variable {
+                    name,
+                    dimensions,
+                    attributes,
+                    r#type,
+                    size,
+                    begin,
+                })
+            })
+            .collect()
+    }
+}
+
+fn trim(bytes: &[u8]) -> Cow<'_, str> {
+    let end = bytes
+        .iter()
+        .rposition(|&byte| byte != 0 && !byte.is_ascii_whitespace())
+        .map_or(0, |index| index + 1);
+    String::from_utf8_lossy(&bytes[..end])
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Attribute or variable values, big endian
+    pub(crate) struct Values {
+        r#type: Type,
+        bytes: Vec<u8>,
+    }
+
+    impl Values {
+        pub(crate) fn text(text: &str) -> Self {
+            Self {
+                r#type: Type::Char,
+                bytes: text.as_bytes().to_vec(),
+            }
+        }
+
+        pub(crate) fn shorts(values: &[i16]) -> Self {
+            Self {
+                r#type: Type::Short,
+                bytes: values
+                    .iter()
+                    .flat_map(|value| value.to_be_bytes())
+                    .collect(),
+            }
+        }
+
+        pub(crate) fn ints(values: &[i32]) -> Self {
+            Self {
+                r#type: Type::Int,
+                bytes: values
+                    .iter()
+                    .flat_map(|value| value.to_be_bytes())
+                    .collect(),
+            }
+        }
+
+        pub(crate) fn floats(values: &[f32]) -> Self {
+            Self {
+                r#type: Type::Float,
+                bytes: values
+                    .iter()
+                    .flat_map(|value| value.to_be_bytes())
+                    .collect(),
+            }
+        }
+
+        pub(crate) fn doubles(values: &[f64]) -> Self {
+            Self {
+                r#type: Type::Double,
+                bytes: values
+                    .iter()
+                    .flat_map(|value| value.to_be_bytes())
+                    .collect(),
+            }
+        }
+
+        fn code(&self) -> u32 {
+            match self.r#type {
+                Type::Byte => 1,
+                Type::Char => 2,
+                Type::Short => 3,
+                Type::Int => 4,
+                Type::Float => 5,
+                Type::Double => 6,
+            }
+        }
+    }
+
+    struct Variable {
+        name: String,
+        dimensions: Vec<u32>,
+        attributes: Vec<(String, Values)>,
+        values: Values,
+    }
+
+    /// netCDF classic (CDF-1) writer
+    #[derive(Default)]
+    pub(crate) struct Writer {
+        records: u32,
+        dimensions: Vec<(String, u32)>,
+        attributes: Vec<(String, Values)>,
+        variables: Vec<Variable>,
+    }
+
+    impl Writer {
+        pub(crate) fn records(mut self, records: u32) -> Self {
+            self.records = records;
+            self
+        }
+
+        /// Dimension, zero length for the record dimension
+        pub(crate) fn dimension(mut self, name: &str, length: u32) -> Self {
+            self.dimensions.push((name.to_owned(), length));
+            self
+        }
+
+        pub(crate) fn attribute(mut self, name: &str, values: Values) -> Self {
+            self.attributes.push((name.to_owned(), values));
+            self
+        }
+
+        pub(crate) fn variable(
+            mut self,
+            name: &str,
+            dimensions: &[u32],
+            attributes: Vec<(&str, Values)>,
+            values: Values,
+        ) -> Self {
+            self.variables.push(Variable {
+                name: name.to_owned(),
+                dimensions: dimensions.to_vec(),
+                attributes: attributes
+                    .into_iter()
+                    .map(|(name, values)| (name.to_owned(), values))
+                    .collect(),
+                values,
+            });
+            self
+        }
+
+        pub(crate) fn write(&self) -> Vec<u8> {
+            let records = match self.records {
+                STREAMING => self.streamed(),
+                records => records,
+            };
+            let records = records as usize;
+            let is_record = |variable: &Variable| {
+                variable
+                    .dimensions
+                    .first()
+                    .is_some_and(|&index| self.dimensions[index as usize].1 == 0)
+            };
+            // Bytes of a record variable per record, or of a whole variable
+            let length = |variable: &Variable| {
+                if is_record(variable) {
+                    variable.values.bytes.len() / records.max(1)
+                } else {
+                    variable.values.bytes.len()
+                }
+            };
+            let single = self
+                .variables
+                .iter()
+                .filter(|&variable| is_record(variable))
+                .count()
+                == 1;
+            // A single record variable is not padded
+            let size = |variable: &Variable| {
+                if single && is_record(variable) {
+                    length(variable)
+                } else {
+                    length(variable).next_multiple_of(4)
+                }
+            };
+            let mut bytes = b"CDF\x01".to_vec();
+            bytes.extend(self.records.to_be_bytes());
+            list(&mut bytes, NC_DIMENSION, self.dimensions.len());
+            for (name, length) in &self.dimensions {
+                string(&mut bytes, name);
+                bytes.extend(length.to_be_bytes());
+            }
+            attributes(&mut bytes, &self.attributes);
+            list(&mut bytes, NC_VARIABLE, self.variables.len());
+            let mut begins = Vec::new();
+            for variable in &self.variables {
+                string(&mut bytes, &variable.name);
+                bytes.extend((variable.dimensions.len() as u32).to_be_bytes());
+                for dimension in &variable.dimensions {
+                    bytes.extend(dimension.to_be_bytes());
+                }
+                attributes(&mut bytes, &variable.attributes);
+                bytes.extend(variable.values.code().to_be_bytes());
+                bytes.extend((size(variable) as u32).to_be_bytes());
+                begins.push(bytes.len());
+                bytes.extend(0u32.to_be_bytes());
+            }
+            let begin = |bytes: &mut Vec<u8>, index: usize, offset: usize| {
+                let position = begins[index];
+                bytes[position..position + 4].copy_from_slice(&(offset as u32).to_be_bytes());
+            };
+            for (index, variable) in self.variables.iter().enumerate() {
+                if !is_record(variable) {
+                    let offset = bytes.len();
+                    begin(&mut bytes, index, offset);
+                    bytes.extend(&variable.values.bytes);
+                    bytes.resize(bytes.len().next_multiple_of(4), 0);
+                }
+            }
+            let start = bytes.len();
+            let record_size = self
+                .variables
+                .iter()
+                .filter(|&variable| is_record(variable))
+                .map(size)
+                .sum::<usize>();
+            bytes.resize(start + records * record_size, 0);
+            let mut offset = start;
+            for (index, variable) in self.variables.iter().enumerate() {
+                if is_record(variable) {
+                    begin(&mut bytes, index, offset);
+                    let length = length(variable);
+                    for record in 0..records {
+                        let position = offset + record * record_size;
+                        bytes[position..position + length].copy_from_slice(
+                            &variable.values.bytes[record * length..(record + 1) * length],
+                        );
+                    }
+                    offset += size(variable);
+                }
+            }
+            bytes
+        }
+
+        /// Number of records of a streamed file, from the record variables
+        fn streamed(&self) -> u32 {
+            self.variables
+                .iter()
+                .filter(|variable| {
+                    variable
+                        .dimensions
+                        .first()
+                        .is_some_and(|&index| self.dimensions[index as usize].1 == 0)
+                })
+                .map(|variable| {
+                    let length = variable.dimensions[1..]
+                        .iter()
+                        .map(|&index| self.dimensions[index as usize].1 as usize)
+                        .product::<usize>()
+                        * variable.values.r#type.size();
+                    (variable.values.bytes.len() / length) as u32
+                })
+                .max()
+                .unwrap_or_default()
+        }
+    }
+
+    fn list(bytes: &mut Vec<u8>, tag: u32, count: usize) {
+        let tag = if count == 0 { ABSENT } else { tag };
+        bytes.extend(tag.to_be_bytes());
+        bytes.extend((count as u32).to_be_bytes());
+    }
+
+    fn string(bytes: &mut Vec<u8>, string: &str) {
+        bytes.extend((string.len() as u32).to_be_bytes());
+        bytes.extend(string.as_bytes());
+        bytes.resize(bytes.len().next_multiple_of(4), 0);
+    }
+
+    fn attributes(bytes: &mut Vec<u8>, attributes: &[(String, Values)]) {
+        list(bytes, NC_ATTRIBUTE, attributes.len());
+        for (name, values) in attributes {
+            string(bytes, name);
+            bytes.extend(values.code().to_be_bytes());
+            let count = values.bytes.len() / values.r#type.size();
+            bytes.extend((count as u32).to_be_bytes());
+            bytes.extend(&values.bytes);
+            bytes.resize(bytes.len().next_multiple_of(4), 0);
+        }
+    }
+
+    #[test]
+    fn variables() -> Result<()> {
+        let bytes = Writer::default()
+            .dimension("points", 3)
+            .dimension("text", 8)
+            .attribute("title", Values::text("Run\0\0"))
+            .attribute("version", Values::doubles(&[2.5]))
+            .variable(
+                "doubles",
+                &[0],
+                Vec::new(),
+                Values::doubles(&[1.5, -2.0, 3.25]),
+            )
+            .variable("shorts", &[0], Vec::new(), Values::shorts(&[1, -2, 3]))
+            .variable(
+                "ints",
+                &[0],
+                vec![("scale_factor", Values::doubles(&[0.5]))],
+                Values::ints(&[2, 4, -6]),
+            )
+            .variable("floats", &[0], Vec::new(), Values::floats(&[0.5, 1.0, 1.5]))
+            .variable("name", &[1], Vec::new(), Values::text("Model\0\0\0"))
+            .write();
+        let file = File::new(&bytes)?;
+        assert_eq!(file.attribute("title").and_then(Value::text), Some("Run"));
+        assert_eq!(file.attribute("version").and_then(Value::first), Some(2.5));
+        assert_eq!(file.values("doubles")?, [1.5, -2.0, 3.25]);
+        assert_eq!(file.values("shorts")?, [1.0, -2.0, 3.0]);
+        assert_eq!(file.values("ints")?, [1.0, 2.0, -3.0]);
+        assert_eq!(file.values("floats")?, [0.5, 1.0, 1.5]);
+        assert_eq!(file.text("name").as_deref(), Some("Model"));
+        assert!(file.values("missing").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn records() -> Result<()> {
+        let bytes = Writer::default()
+            .records(3)
+            .dimension("time", 0)
+            .dimension("pair", 2)
+            .variable("times", &[0], Vec::new(), Values::doubles(&[1.0, 2.0, 3.0]))
+            .variable("shorts", &[0], Vec::new(), Values::shorts(&[10, 20, 30]))
+            .variable(
+                "pairs",
+                &[0, 1],
+                Vec::new(),
+                Values::ints(&[1, 2, 3, 4, 5, 6]),
+            )
+            .write();
+        let file = File::new(&bytes)?;
+        assert_eq!(file.values("times")?, [1.0, 2.0, 3.0]);
+        assert_eq!(file.values("shorts")?, [10.0, 20.0, 30.0]);
+        assert_eq!(file.values("pairs")?, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn streaming() -> Result<()> {
+        let bytes = Writer::default()
+            .records(STREAMING)
+            .dimension("time", 0)
+            .variable("shorts", &[0], Vec::new(), Values::shorts(&[1, 2, 3, 4, 5]))
+            .write();
+        let file = File::new(&bytes)?;
+        assert_eq!(file.values("shorts")?, [1.0, 2.0, 3.0, 4.0, 5.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn truncated() -> Result<()> {
+        assert!(!is_netcdf(b"CDF\x05"));
+        assert!(File::new(b"CDF\x01\0\0").is_err());
+        let bytes = Writer::default()
+            .dimension("points", 4)
+            .variable("doubles", &[0], Vec::new(), Values::doubles(&[1.0; 4]))
+            .write();
+        let file = File::new(&bytes[..bytes.len() - 8])?;
+        assert!(file.values("doubles").is_err());
+        Ok(())
+    }
+}
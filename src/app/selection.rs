@@ -0,0 +1,79 @@
+use egui::{Context, Id, Response, Ui};
+use egui_plot::{PlotPoint, PlotUi, VLine};
+
+const ID: &str = "Selection";
+const MASS_TO_CHARGE: &str = "MassToCharge";
+
+/// Retention time selection, minutes
+///
+/// A single retention time when the start and the end are equal. The
+/// selection is scoped by [`scope`](crate::app::link::scope), the link of the
+/// pane or its run.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct Selection {
+    pub(crate) start: f64,
+    pub(crate) end: f64,
+}
+
+impl Selection {
+    pub(crate) fn new(start: f64, end: f64) -> Self {
+        Self {
+            start: start.min(end),
+            end: start.max(end),
+        }
+    }
+
+    /// Selection stored in the context
+    pub(crate) fn get(ctx: &Context, scope: Id) -> Option<Self> {
+        ctx.data(|data| data.get_temp(scope.with(ID)))
+    }
+
+    pub(crate) fn set(ctx: &Context, scope: Id, selection: Self) {
+        ctx.data_mut(|data| data.insert_temp(scope.with(ID), selection));
+    }
+
+    /// Draws the selection on a retention time axis
+    pub(crate) fn show(&self, ui: &mut PlotUi) {
+        ui.vline(VLine::new("Selection", self.start));
+        if self.is_range() {
+            ui.vline(VLine::new("Selection", self.end));
+        }
+    }
+
+    /// Selects the retention time clicked on the plot, or the range dragged
+    /// with shift held
+    pub(crate) fn select(ui: &Ui, scope: Id, response: &Response, pointer: Option<PlotPoint>) {
+        let Some(pointer) = pointer else {
+            return;
+        };
+        let id = response.id.with("SelectionStart");
+        if response.clicked() {
+            Self::set(ui.ctx(), scope, Self::new(pointer.x, pointer.x));
+        } else if response.drag_started() && ui.input(|input| input.modifiers.shift) {
+            ui.data_mut(|data| data.insert_temp(id, pointer.x));
+        } else if response.drag_stopped()
+            && let Some(start) = ui.data_mut(|data| data.remove_temp::<f64>(id))
+        {
+            Self::set(ui.ctx(), scope, Self::new(start, pointer.x));
+        }
+    }
+
+    /// Whether the selection is a range
+    pub(crate) fn is_range(&self) -> bool {
+        self.start < self.end
+    }
+
+    /// Whether the retention time is selected
+    pub(crate) fn contains(&self, retention_time: f64) -> bool {
+        self.start <= retention_time && retention_time <= self.end
+    }
+}
+
+/// Selected mass to charge stored in the context
+pub(crate) fn mass_to_charge(ctx: &Context, scope: Id) -> Option<f32> {
+    ctx.data(|data| data.get_temp(scope.with(MASS_TO_CHARGE)))
+}
+
+pub(crate) fn select_mass_to_charge(ctx: &Context, scope: Id, mass_to_charge: f32) {
+    ctx.data_mut(|data| data.insert_temp(scope.with(MASS_TO_CHARGE), mass_to_charge));
+}
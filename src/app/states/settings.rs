@@ -1,5 +1,9 @@
 use crate::app::MAX_PRECISION;
-use egui::{ComboBox, DragValue, Grid, Response, Ui, Widget, WidgetText, emath::Float};
+use egui::{
+    Color32, ComboBox, DragValue, Grid, Response, TextEdit, Ui, Widget, WidgetText, emath::Float,
+};
+use egui_ext::color;
+use egui_phosphor::regular::{MINUS, PLUS};
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::{self, Display, Formatter},
@@ -10,8 +14,12 @@ use uom::si::{
     time::{Units, millisecond, minute, second},
 };
 
+/// Largest filter half window (points)
+const MAX_WINDOW: usize = 10_000;
+
 /// Settings
-#[derive(Clone, Copy, Debug, Deserialize, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Hash, PartialEq, Serialize)]
+#[serde(default)]
 pub(crate) struct Settings {
     pub(crate) explode: bool,
     pub(crate) filter_null: bool,
@@ -26,7 +34,19 @@ pub(crate) struct Settings {
     pub(crate) min_periods: usize,
 
     pub(crate) sort: Sort,
+    pub(crate) binning: Binning,
+    pub(crate) smoothing: Smoothing,
+    pub(crate) baseline: Baseline,
+    pub(crate) peaks: Peaks,
     pub(crate) plot: Plot,
+    pub(crate) search: Search,
+    pub(crate) retention_index: RetentionIndex,
+    pub(crate) comparison: Comparison,
+    pub(crate) extraction: Extraction,
+    pub(crate) heat_map: HeatMap,
+    pub(crate) spectrum: Spectrum,
+    /// Link group
+    pub(crate) link: Option<usize>,
 
     pub(crate) visible: Option<bool>,
 }
@@ -44,7 +64,18 @@ impl Settings {
             window_size: 3,
             min_periods: 1,
             sort: Sort::default(),
+            binning: Binning::new(),
+            smoothing: Smoothing::new(),
+            baseline: Baseline::new(),
+            peaks: Peaks::new(),
             plot: Plot::new(),
+            search: Search::new(),
+            retention_index: RetentionIndex::new(),
+            comparison: Comparison::new(),
+            extraction: Extraction::new(),
+            heat_map: HeatMap::new(),
+            spectrum: Spectrum::new(),
+            link: None,
             visible: None,
         }
     }
@@ -57,7 +88,8 @@ impl Default for Settings {
 }
 
 impl Settings {
-    pub(crate) fn ui(&mut self, ui: &mut Ui) {
+    /// Shows the sections relevant to the kind of the pane
+    pub(crate) fn ui(&mut self, ui: &mut Ui, kind: Kind) {
         Grid::new(ui.next_auto_id()).show(ui, |ui| {
             // Retention time
             ui.label("Retention time");
@@ -99,6 +131,8 @@ impl Settings {
                 .on_hover_text("Mass to charge precision");
             ui.end_row();
 
+            self.binning(ui);
+
             // Signal
             ui.label("Signal");
             ui.checkbox(&mut self.signal.normalize, "Normalize");
@@ -106,25 +140,62 @@ impl Settings {
                 .on_hover_text("Signal precision");
             ui.end_row();
 
-            ui.label("Explode");
-            ui.checkbox(&mut self.explode, "")
-                .on_hover_text("Explode lists");
-            ui.end_row();
+            // Table and plot panes show the frame as sorted and exploded
+            if matches!(kind, Kind::Plot | Kind::Table) {
+                ui.label("Explode");
+                ui.checkbox(&mut self.explode, "")
+                    .on_hover_text("Explode lists");
+                ui.end_row();
+            }
 
-            ui.label("Filter empty/null");
-            ui.checkbox(&mut self.filter_null, "")
-                .on_hover_text("Filter empty/null retention time");
-            ui.end_row();
+            if kind.computes_table() {
+                ui.label("Filter empty/null");
+                ui.checkbox(&mut self.filter_null, "")
+                    .on_hover_text("Filter empty/null retention time");
+                ui.end_row();
+            }
 
-            self.sort(ui);
-            self.peak_max(ui);
-            self.window_size(ui);
-            self.min_periods(ui);
+            if matches!(kind, Kind::Plot | Kind::Table) {
+                self.sort(ui);
+                self.peak_max(ui);
+                self.window_size(ui);
+                self.min_periods(ui);
+            }
 
-            self.legend(ui);
-            self.stack(ui);
-            self.bar_sort(ui);
-            self.bar_width(ui);
+            if matches!(kind, Kind::Comparison | Kind::Plot) {
+                self.legend(ui);
+            }
+            if kind == Kind::Plot {
+                self.stack(ui);
+                self.bar_sort(ui);
+                self.bar_width(ui);
+            }
+
+            if kind.computes_table() {
+                self.smoothing(ui);
+                self.baseline(ui);
+            }
+            if matches!(kind, Kind::Peaks | Kind::Plot | Kind::Table) {
+                self.peaks(ui);
+            }
+            if kind == Kind::Search {
+                self.search(ui);
+            }
+            if matches!(kind, Kind::Peaks | Kind::Plot | Kind::Table) {
+                self.retention_index(ui);
+            }
+            if kind == Kind::Comparison {
+                self.comparison(ui);
+            }
+            if kind == Kind::Plot {
+                self.extraction(ui);
+            }
+            if matches!(kind, Kind::HeatMap | Kind::Plot) {
+                self.heat_map(ui);
+            }
+            if kind == Kind::Spectrum {
+                self.spectrum(ui);
+            }
 
             // ui.horizontal(|ui| {
             //     ui.selectable_value(&mut self.visible, Some(true), "◉👁");
@@ -157,6 +228,52 @@ impl Settings {
         ui.end_row();
     }
 
+    /// Mass to charge binning
+    fn binning(&mut self, ui: &mut Ui) {
+        ui.label("Mass to charge binning");
+        ComboBox::from_id_salt("MassToChargeBinning")
+            .selected_text(self.binning.mode.text())
+            .show_ui(ui, |ui| {
+                for mode in [Mode::Decimals, Mode::Absolute, Mode::Ppm, Mode::Nominal] {
+                    ui.selectable_value(&mut self.binning.mode, mode, mode.text())
+                        .on_hover_text(mode.description());
+                }
+            })
+            .response
+            .on_hover_text(self.binning.mode.description());
+        match self.binning.mode {
+            Mode::Decimals => {
+                ui.add(DragValue::new(&mut self.binning.decimals).range(0..=MAX_PRECISION))
+                    .on_hover_text("Decimal places");
+            }
+            Mode::Absolute => {
+                ui.add(
+                    DragValue::new(&mut self.binning.width)
+                        .range(0.0001..=f64::MAX)
+                        .speed(0.001),
+                )
+                .on_hover_text("Bin width, Da");
+            }
+            Mode::Ppm => {
+                ui.add(
+                    DragValue::new(&mut self.binning.ppm)
+                        .range(0.1..=f64::MAX)
+                        .speed(1.0),
+                )
+                .on_hover_text("Bin width, ppm");
+            }
+            Mode::Nominal => {
+                ui.add(
+                    DragValue::new(&mut self.binning.offset)
+                        .range(0.0..=1.0)
+                        .speed(0.01),
+                )
+                .on_hover_text("Offset, the bin of mass n is [n - offset, n + 1 - offset)");
+            }
+        }
+        ui.end_row();
+    }
+
     /// Peak min max
     fn peak_max(&mut self, ui: &mut Ui) {
         ui.label("PeakMinMax");
@@ -240,6 +357,1122 @@ impl Settings {
             .on_hover_text("Stack.hover");
         ui.end_row();
     }
+
+    /// Smoothing
+    fn smoothing(&mut self, ui: &mut Ui) {
+        ui.label("Smoothing");
+        ComboBox::from_id_salt("Smoother")
+            .selected_text(self.smoothing.smoother.text())
+            .show_ui(ui, |ui| {
+                for smoother in [
+                    Smoother::None,
+                    Smoother::SavitzkyGolay,
+                    Smoother::Gaussian,
+                    Smoother::Whittaker,
+                ] {
+                    ui.selectable_value(&mut self.smoothing.smoother, smoother, smoother.text())
+                        .on_hover_text(smoother.description());
+                }
+            })
+            .response
+            .on_hover_text(self.smoothing.smoother.description());
+        ui.end_row();
+
+        match self.smoothing.smoother {
+            Smoother::None => {}
+            Smoother::SavitzkyGolay => {
+                ui.label("Smoothing window");
+                ui.add(DragValue::new(&mut self.smoothing.window).range(1..=MAX_WINDOW))
+                    .on_hover_text("Half window, points");
+                ui.end_row();
+
+                ui.label("Smoothing order");
+                ui.add(
+                    DragValue::new(&mut self.smoothing.order).range(0..=2 * self.smoothing.window),
+                )
+                .on_hover_text("Polynomial order");
+                ui.end_row();
+            }
+            Smoother::Gaussian => {
+                ui.label("Smoothing sigma");
+                ui.add(
+                    DragValue::new(&mut self.smoothing.sigma)
+                        .range(0.0..=MAX_WINDOW as f64)
+                        .speed(0.1),
+                )
+                .on_hover_text("Standard deviation, points");
+                ui.end_row();
+            }
+            Smoother::Whittaker => {
+                ui.label("Smoothing lambda");
+                ui.add(
+                    DragValue::new(&mut self.smoothing.lambda)
+                        .range(0.0..=1e12)
+                        .logarithmic(true),
+                )
+                .on_hover_text("λ, penalty of the second differences");
+                ui.end_row();
+            }
+        }
+    }
+
+    /// Baseline
+    fn baseline(&mut self, ui: &mut Ui) {
+        ui.label("Baseline");
+        ComboBox::from_id_salt("BaselineAlgorithm")
+            .selected_text(self.baseline.algorithm.text())
+            .show_ui(ui, |ui| {
+                for algorithm in [
+                    Algorithm::None,
+                    Algorithm::AsymmetricLeastSquares,
+                    Algorithm::Snip,
+                    Algorithm::RollingMinimum,
+                ] {
+                    ui.selectable_value(&mut self.baseline.algorithm, algorithm, algorithm.text())
+                        .on_hover_text(algorithm.description());
+                }
+            })
+            .response
+            .on_hover_text(self.baseline.algorithm.description());
+        ui.end_row();
+
+        match self.baseline.algorithm {
+            Algorithm::None => {}
+            Algorithm::AsymmetricLeastSquares => {
+                ui.label("Baseline smoothness");
+                ui.add(
+                    DragValue::new(&mut self.baseline.smoothness)
+                        .range(1.0..=1e12)
+                        .logarithmic(true),
+                )
+                .on_hover_text("λ, penalty of the baseline second differences");
+                ui.end_row();
+
+                ui.label("Baseline asymmetry");
+                ui.add(
+                    DragValue::new(&mut self.baseline.asymmetry)
+                        .range(0.0001..=0.5)
+                        .speed(0.001),
+                )
+                .on_hover_text("p, weight of the points above the baseline");
+                ui.end_row();
+
+                ui.label("Baseline iterations");
+                ui.add(DragValue::new(&mut self.baseline.iterations).range(1..=100))
+                    .on_hover_text("Reweighting iterations");
+                ui.end_row();
+            }
+            Algorithm::Snip | Algorithm::RollingMinimum => {
+                ui.label("Baseline window");
+                ui.add(DragValue::new(&mut self.baseline.window).range(1..=MAX_WINDOW))
+                    .on_hover_text("Half window, points");
+                ui.end_row();
+            }
+        }
+    }
+
+    /// Peaks
+    fn peaks(&mut self, ui: &mut Ui) {
+        ui.label("Integrate");
+        ui.checkbox(&mut self.peaks.integrate, "")
+            .on_hover_text("Show the peak integration on the plot");
+        ui.end_row();
+
+        ui.label("Peak slope");
+        ui.add(
+            DragValue::new(&mut self.peaks.slope)
+                .range(0.0..=f64::MAX)
+                .speed(0.01),
+        )
+        .on_hover_text("Slope threshold, fraction of the maximum signal per minute");
+        ui.end_row();
+
+        ui.label("Peak height");
+        ui.add(
+            DragValue::new(&mut self.peaks.min_height)
+                .range(0.0..=1.0)
+                .speed(0.0001),
+        )
+        .on_hover_text("Minimum height, fraction of the maximum signal");
+        ui.end_row();
+
+        ui.label("Peak width");
+        ui.add(DragValue::new(&mut self.peaks.min_width).range(1..=MAX_WINDOW))
+            .on_hover_text("Minimum width, points");
+        ui.end_row();
+
+        ui.label("Peak window");
+        ui.add(DragValue::new(&mut self.peaks.window).range(1..=MAX_WINDOW))
+            .on_hover_text("Derivative half window, points");
+        ui.end_row();
+
+        ui.label("Peak split");
+        ComboBox::from_id_salt("PeakSplit")
+            .selected_text(self.peaks.split.text())
+            .show_ui(ui, |ui| {
+                for split in [Split::Drop, Split::Valley] {
+                    ui.selectable_value(&mut self.peaks.split, split, split.text())
+                        .on_hover_text(split.description());
+                }
+            })
+            .response
+            .on_hover_text(self.peaks.split.description());
+        ui.end_row();
+
+        ui.label("Peak skim");
+        ui.add(
+            DragValue::new(&mut self.peaks.skim)
+                .range(0.0..=1.0)
+                .speed(0.01),
+        )
+        .on_hover_text("Tangent skim riders lower than this fraction of the preceding peak");
+        ui.end_row();
+    }
+
+    /// Search
+    fn search(&mut self, ui: &mut Ui) {
+        ui.label("Search hits");
+        ui.add(DragValue::new(&mut self.search.top).range(1..=usize::MAX))
+            .on_hover_text("Number of library hits to show");
+        ui.end_row();
+
+        ui.label("Search weights");
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(&mut self.search.mass_to_charge_weight)
+                    .range(0.0..=3.0)
+                    .speed(0.1),
+            )
+            .on_hover_text("Mass to charge exponent");
+            ui.add(
+                DragValue::new(&mut self.search.signal_weight)
+                    .range(0.0..=1.0)
+                    .speed(0.1),
+            )
+            .on_hover_text("Signal exponent");
+        });
+        ui.end_row();
+    }
+
+    /// Retention index
+    fn retention_index(&mut self, ui: &mut Ui) {
+        ui.label("Retention index");
+        ComboBox::from_id_salt("RetentionIndexInterpolation")
+            .selected_text(self.retention_index.interpolation.text())
+            .show_ui(ui, |ui| {
+                for interpolation in [Interpolation::Linear, Interpolation::Logarithmic] {
+                    ui.selectable_value(
+                        &mut self.retention_index.interpolation,
+                        interpolation,
+                        interpolation.text(),
+                    )
+                    .on_hover_text(interpolation.description());
+                }
+            })
+            .response
+            .on_hover_text(self.retention_index.interpolation.description());
+        ui.end_row();
+
+        ui.label("Plot axis");
+        ComboBox::from_id_salt("RetentionIndexAxis")
+            .selected_text(self.retention_index.axis.text())
+            .show_ui(ui, |ui| {
+                for axis in [
+                    Axis::RetentionTime,
+                    Axis::RetentionIndex,
+                    Axis::EquivalentChainLength,
+                ] {
+                    ui.selectable_value(&mut self.retention_index.axis, axis, axis.text())
+                        .on_hover_text(axis.description());
+                }
+            })
+            .response
+            .on_hover_text(self.retention_index.axis.description());
+        ui.end_row();
+    }
+
+    /// Comparison
+    fn comparison(&mut self, ui: &mut Ui) {
+        ui.label("Comparison");
+        ComboBox::from_id_salt("ComparisonTrace")
+            .selected_text(self.comparison.trace.text())
+            .show_ui(ui, |ui| {
+                for trace in [Trace::TotalIonChromatogram, Trace::ExtractedIonChromatogram] {
+                    ui.selectable_value(&mut self.comparison.trace, trace, trace.text())
+                        .on_hover_text(trace.description());
+                }
+            })
+            .response
+            .on_hover_text(self.comparison.trace.description());
+        ui.end_row();
+
+        if self.comparison.trace == Trace::ExtractedIonChromatogram {
+            ui.label("Comparison ion");
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut self.comparison.mass_to_charge)
+                        .range(0.0..=f64::MAX)
+                        .speed(0.1),
+                )
+                .on_hover_text("Mass to charge");
+                ui.label("±");
+                ui.add(
+                    DragValue::new(&mut self.comparison.tolerance)
+                        .range(0.0..=f64::MAX)
+                        .speed(0.01),
+                )
+                .on_hover_text("Mass to charge tolerance, Da");
+            });
+            ui.end_row();
+        }
+
+        ui.label("Comparison normalization");
+        ComboBox::from_id_salt("ComparisonNormalization")
+            .selected_text(self.comparison.normalization.text())
+            .show_ui(ui, |ui| {
+                for normalization in [
+                    Normalization::None,
+                    Normalization::Maximum,
+                    Normalization::Area,
+                ] {
+                    ui.selectable_value(
+                        &mut self.comparison.normalization,
+                        normalization,
+                        normalization.text(),
+                    )
+                    .on_hover_text(normalization.description());
+                }
+            })
+            .response
+            .on_hover_text(self.comparison.normalization.description());
+        ui.end_row();
+
+        ui.label("Comparison offset");
+        ui.add(
+            DragValue::new(&mut self.comparison.offset)
+                .range(0.0..=f64::MAX)
+                .speed(0.01),
+        )
+        .on_hover_text(
+            "Vertical offset between the runs, fraction of the highest trace: 0 overlays, 1 stacks",
+        );
+        ui.end_row();
+    }
+
+    fn heat_map(&mut self, ui: &mut Ui) {
+        ui.label("Heat map");
+        ComboBox::from_id_salt("HeatMapColormap")
+            .selected_text(self.heat_map.colormap.text())
+            .show_ui(ui, |ui| {
+                for colormap in [Colormap::Viridis, Colormap::Inferno, Colormap::Grayscale] {
+                    ui.selectable_value(&mut self.heat_map.colormap, colormap, colormap.text());
+                }
+            })
+            .response
+            .on_hover_text("Colormap");
+        ui.checkbox(&mut self.heat_map.logarithmic, "Logarithmic")
+            .on_hover_text("Logarithmic color scale");
+        ui.end_row();
+
+        ui.label("Heat map resolution");
+        ui.add(DragValue::new(&mut self.heat_map.resolution).range(16..=1024))
+            .on_hover_text("Number of bins along each axis of the visible region");
+        ui.end_row();
+    }
+
+    /// Mass spectrum
+    fn spectrum(&mut self, ui: &mut Ui) {
+        ui.label("Spectrum labels");
+        ui.add(DragValue::new(&mut self.spectrum.labels))
+            .on_hover_text("Number of the highest peaks labeled with their mass to charge");
+        ui.end_row();
+
+        ui.label("Spectrum background");
+        ui.checkbox(&mut self.spectrum.subtraction, "Subtract")
+            .on_hover_text("Subtract the mean spectrum of the regions flanking the selection");
+        ui.add_enabled(
+            self.spectrum.subtraction,
+            DragValue::new(&mut self.spectrum.flank)
+                .range(0.0..=f64::MAX)
+                .speed(0.01),
+        )
+        .on_hover_text("Width of each flanking region, minutes");
+        ui.end_row();
+    }
+
+    /// Extracted ion chromatograms
+    fn extraction(&mut self, ui: &mut Ui) {
+        ui.label("Extracted ions");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.extraction.plot, "Plot")
+                .on_hover_text("Plot the extracted ion chromatograms of the ions");
+            ui.checkbox(&mut self.extraction.sum, "Sum")
+                .on_hover_text("Plot the sum of the extracted ion chromatograms");
+            if ui.button(PLUS).on_hover_text("Add an ion").clicked() {
+                let index = self.extraction.ions.len();
+                self.extraction.ions.push(Ion::new(color(index)));
+            }
+        });
+        ui.end_row();
+
+        let mut remove = None;
+        for (index, ion) in self.extraction.ions.iter_mut().enumerate() {
+            ui.label("");
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(&mut ion.color);
+                ui.add(
+                    DragValue::new(&mut ion.mass_to_charge)
+                        .range(0.0..=f64::MAX)
+                        .speed(0.1),
+                )
+                .on_hover_text("Mass to charge");
+                ui.label("±");
+                ui.add(
+                    DragValue::new(&mut ion.tolerance)
+                        .range(0.0..=f64::MAX)
+                        .speed(0.01),
+                )
+                .on_hover_text("Mass to charge tolerance, Da");
+                ui.add(
+                    TextEdit::singleline(&mut ion.label)
+                        .hint_text("Label")
+                        .desired_width(64.0),
+                );
+                if ui.button(MINUS).on_hover_text("Remove the ion").clicked() {
+                    remove = Some(index);
+                }
+            });
+            ui.end_row();
+        }
+        if let Some(index) = remove {
+            self.extraction.ions.remove(index);
+        }
+    }
+}
+
+/// Kind of the pane the settings belong to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Kind {
+    Comparison,
+    HeatMap,
+    Peaks,
+    Plot,
+    Search,
+    Spectrum,
+    Table,
+}
+
+impl Kind {
+    /// Whether the pane shows the frame of the table computer, the heat map
+    /// and the spectrum panes read the loaded points
+    fn computes_table(&self) -> bool {
+        !matches!(self, Self::HeatMap | Self::Spectrum)
+    }
+}
+
+/// Mass to charge binning settings
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct Binning {
+    pub(crate) mode: Mode,
+    pub(crate) decimals: usize,
+    /// Absolute bin width, Da
+    pub(crate) width: f64,
+    /// Relative bin width, ppm
+    pub(crate) ppm: f64,
+    /// Nominal mass offset
+    pub(crate) offset: f64,
+}
+
+impl Binning {
+    fn new() -> Self {
+        Self {
+            mode: Mode::Decimals,
+            decimals: 2,
+            width: 0.01,
+            ppm: 10.0,
+            offset: 0.3,
+        }
+    }
+
+    /// Bin center of the mass to charge
+    pub(crate) fn bin(&self, mass_to_charge: f32) -> f32 {
+        let value = mass_to_charge as f64;
+        let binned = match self.mode {
+            Mode::Decimals => {
+                let scale = 10f64.powi(self.decimals as _);
+                (value * scale).round_ties_even() / scale
+            }
+            Mode::Absolute if self.width > 0.0 => (value / self.width).round() * self.width,
+            Mode::Ppm if self.ppm > 0.0 && value > 0.0 => {
+                let step = (self.ppm * 1e-6).ln_1p();
+                ((value.ln() / step).round() * step).exp()
+            }
+            Mode::Nominal => (value + self.offset).floor(),
+            _ => value,
+        };
+        binned as _
+    }
+}
+
+impl Default for Binning {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash for Binning {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.mode.hash(state);
+        self.decimals.hash(state);
+        self.width.ord().hash(state);
+        self.ppm.ord().hash(state);
+        self.offset.ord().hash(state);
+    }
+}
+
+/// Mass to charge binning mode
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum Mode {
+    #[default]
+    Decimals,
+    Absolute,
+    Ppm,
+    Nominal,
+}
+
+impl Mode {
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::Decimals => "Decimals",
+            Self::Absolute => "Da",
+            Self::Ppm => "ppm",
+            Self::Nominal => "Nominal",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Self::Decimals => "Round to a fixed number of decimal places",
+            Self::Absolute => "Bins of a fixed width in daltons",
+            Self::Ppm => "Bins of a fixed relative width in parts per million",
+            Self::Nominal => "Integer masses with an offset, for unit resolution instruments",
+        }
+    }
+}
+
+/// Smoothing settings
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct Smoothing {
+    pub(crate) smoother: Smoother,
+    /// Savitzky–Golay half window
+    pub(crate) window: usize,
+    /// Savitzky–Golay polynomial order
+    pub(crate) order: usize,
+    /// Gaussian standard deviation
+    pub(crate) sigma: f64,
+    /// Whittaker λ
+    pub(crate) lambda: f64,
+}
+
+impl Smoothing {
+    fn new() -> Self {
+        Self {
+            smoother: Smoother::None,
+            window: 3,
+            order: 2,
+            sigma: 1.5,
+            lambda: 10.0,
+        }
+    }
+}
+
+impl Default for Smoothing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash for Smoothing {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.smoother.hash(state);
+        self.window.hash(state);
+        self.order.hash(state);
+        self.sigma.ord().hash(state);
+        self.lambda.ord().hash(state);
+    }
+}
+
+/// Smoother
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum Smoother {
+    #[default]
+    None,
+    SavitzkyGolay,
+    Gaussian,
+    Whittaker,
+}
+
+impl Smoother {
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::SavitzkyGolay => "Savitzky–Golay",
+            Self::Gaussian => "Gaussian",
+            Self::Whittaker => "Whittaker",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Self::None => "No smoothing",
+            Self::SavitzkyGolay => "Local polynomial least squares fit",
+            Self::Gaussian => "Gaussian kernel convolution",
+            Self::Whittaker => "Penalized least squares smoother",
+        }
+    }
+}
+
+/// Baseline correction settings
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct Baseline {
+    pub(crate) algorithm: Algorithm,
+    /// Asymmetric least squares λ
+    pub(crate) smoothness: f64,
+    /// Asymmetric least squares p
+    pub(crate) asymmetry: f64,
+    pub(crate) iterations: usize,
+    /// SNIP and rolling minimum half window
+    pub(crate) window: usize,
+}
+
+impl Baseline {
+    fn new() -> Self {
+        Self {
+            algorithm: Algorithm::None,
+            smoothness: 1e5,
+            asymmetry: 0.01,
+            iterations: 10,
+            window: 50,
+        }
+    }
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash for Baseline {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.algorithm.hash(state);
+        self.smoothness.ord().hash(state);
+        self.asymmetry.ord().hash(state);
+        self.iterations.hash(state);
+        self.window.hash(state);
+    }
+}
+
+/// Baseline algorithm
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum Algorithm {
+    #[default]
+    None,
+    AsymmetricLeastSquares,
+    Snip,
+    RollingMinimum,
+}
+
+impl Algorithm {
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::AsymmetricLeastSquares => "Asymmetric least squares",
+            Self::Snip => "SNIP",
+            Self::RollingMinimum => "Rolling minimum",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Self::None => "No baseline correction",
+            Self::AsymmetricLeastSquares => "Smooth baseline fitted with asymmetric weights",
+            Self::Snip => "Statistics-sensitive non-linear iterative peak-clipping",
+            Self::RollingMinimum => "Smoothed rolling minimum",
+        }
+    }
+}
+
+/// Peak detection and integration settings
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct Peaks {
+    pub(crate) integrate: bool,
+    /// Slope threshold relative to the maximum signal (per minute)
+    pub(crate) slope: f64,
+    /// Minimum height relative to the maximum signal
+    pub(crate) min_height: f64,
+    pub(crate) min_width: usize,
+    /// Derivative half window
+    pub(crate) window: usize,
+    pub(crate) split: Split,
+    /// Rider to parent height ratio below which riders are skimmed
+    pub(crate) skim: f64,
+}
+
+impl Peaks {
+    fn new() -> Self {
+        Self {
+            integrate: false,
+            slope: 0.5,
+            min_height: 0.001,
+            min_width: 3,
+            window: 2,
+            split: Split::Drop,
+            skim: 0.1,
+        }
+    }
+}
+
+impl Default for Peaks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash for Peaks {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.integrate.hash(state);
+        self.slope.ord().hash(state);
+        self.min_height.ord().hash(state);
+        self.min_width.hash(state);
+        self.window.hash(state);
+        self.split.hash(state);
+        self.skim.ord().hash(state);
+    }
+}
+
+/// Fused peaks split
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum Split {
+    #[default]
+    Drop,
+    Valley,
+}
+
+impl Split {
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::Drop => "Drop line",
+            Self::Valley => "Valley to valley",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Self::Drop => "Perpendicular drop from the valley to the common baseline",
+            Self::Valley => "Baseline drawn between the valleys of each peak",
+        }
+    }
+}
+
+/// Search settings
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct Search {
+    pub(crate) top: usize,
+    /// Peak weight is `m/z^a * I^b`, this is `a`
+    pub(crate) mass_to_charge_weight: f64,
+    /// Peak weight is `m/z^a * I^b`, this is `b`
+    pub(crate) signal_weight: f64,
+}
+
+impl Search {
+    fn new() -> Self {
+        Self {
+            top: 10,
+            mass_to_charge_weight: 1.0,
+            signal_weight: 0.5,
+        }
+    }
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash for Search {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.top.hash(state);
+        self.mass_to_charge_weight.ord().hash(state);
+        self.signal_weight.ord().hash(state);
+    }
+}
+
+/// Retention index settings
+#[derive(Clone, Copy, Debug, Deserialize, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct RetentionIndex {
+    pub(crate) interpolation: Interpolation,
+    /// Secondary retention axis of the plot
+    pub(crate) axis: Axis,
+}
+
+impl RetentionIndex {
+    fn new() -> Self {
+        Self {
+            interpolation: Interpolation::Linear,
+            axis: Axis::RetentionTime,
+        }
+    }
+}
+
+impl Default for RetentionIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Retention index interpolation
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum Interpolation {
+    #[default]
+    Linear,
+    Logarithmic,
+}
+
+impl Interpolation {
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::Logarithmic => "Kovats",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Self::Linear => {
+                "Linear retention index (van den Dool and Kratz), temperature programmed"
+            }
+            Self::Logarithmic => {
+                "Logarithmic retention index (Kovats) of the adjusted retention times, isothermal"
+            }
+        }
+    }
+}
+
+/// Retention axis
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum Axis {
+    #[default]
+    RetentionTime,
+    RetentionIndex,
+    EquivalentChainLength,
+}
+
+impl Axis {
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::RetentionTime => "Retention time",
+            Self::RetentionIndex => "Retention index",
+            Self::EquivalentChainLength => "Equivalent chain length",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Self::RetentionTime => "Retention time only",
+            Self::RetentionIndex => "Retention index axis from the calibration",
+            Self::EquivalentChainLength => "Equivalent chain length axis from the calibration",
+        }
+    }
+}
+
+/// Extracted ion chromatograms settings
+#[derive(Clone, Debug, Deserialize, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct Extraction {
+    /// Plot the ions instead of the spectra
+    pub(crate) plot: bool,
+    /// Plot the sum of the ions
+    pub(crate) sum: bool,
+    pub(crate) ions: Vec<Ion>,
+}
+
+impl Extraction {
+    fn new() -> Self {
+        Self {
+            plot: false,
+            sum: false,
+            ions: [74.0, 87.0, 79.0, 81.0]
+                .into_iter()
+                .enumerate()
+                .map(|(index, mass_to_charge)| Ion {
+                    mass_to_charge,
+                    ..Ion::new(color(index))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for Extraction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Extracted ion definition
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct Ion {
+    pub(crate) mass_to_charge: f64,
+    /// Mass to charge tolerance, Da
+    pub(crate) tolerance: f64,
+    pub(crate) label: String,
+    pub(crate) color: Color32,
+}
+
+impl Ion {
+    fn new(color: Color32) -> Self {
+        Self {
+            mass_to_charge: 0.0,
+            tolerance: 0.5,
+            label: String::new(),
+            color,
+        }
+    }
+
+    /// Label, or the mass to charge if empty
+    pub(crate) fn name(&self) -> String {
+        if self.label.is_empty() {
+            format!("{} ± {}", self.mass_to_charge, self.tolerance)
+        } else {
+            self.label.clone()
+        }
+    }
+}
+
+impl Default for Ion {
+    fn default() -> Self {
+        Self::new(color(0))
+    }
+}
+
+impl Hash for Ion {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.mass_to_charge.ord().hash(state);
+        self.tolerance.ord().hash(state);
+        self.label.hash(state);
+        self.color.hash(state);
+    }
+}
+
+/// Heat map settings
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct HeatMap {
+    pub(crate) colormap: Colormap,
+    pub(crate) logarithmic: bool,
+    /// Bins along each axis of the visible region
+    pub(crate) resolution: usize,
+}
+
+impl HeatMap {
+    fn new() -> Self {
+        Self {
+            colormap: Colormap::Viridis,
+            logarithmic: true,
+            resolution: 256,
+        }
+    }
+}
+
+impl Default for HeatMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Colormap
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum Colormap {
+    #[default]
+    Viridis,
+    Inferno,
+    Grayscale,
+}
+
+impl Colormap {
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::Viridis => "Viridis",
+            Self::Inferno => "Inferno",
+            Self::Grayscale => "Grayscale",
+        }
+    }
+
+    /// Color of a fraction in `0..=1`
+    pub(crate) fn color(&self, fraction: f32) -> Color32 {
+        let stops: &[[u8; 3]] = match self {
+            Self::Viridis => &[
+                [68, 1, 84],
+                [59, 82, 139],
+                [33, 145, 140],
+                [94, 201, 98],
+                [253, 231, 37],
+            ],
+            Self::Inferno => &[
+                [0, 0, 4],
+                [87, 16, 110],
+                [188, 55, 84],
+                [249, 142, 9],
+                [252, 255, 164],
+            ],
+            Self::Grayscale => &[[0, 0, 0], [255, 255, 255]],
+        };
+        let position = fraction.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let index = (position as usize).min(stops.len() - 2);
+        let fraction = position - index as f32;
+        let [r, g, b] = [0, 1, 2].map(|channel| {
+            let start = stops[index][channel] as f32;
+            let end = stops[index + 1][channel] as f32;
+            (start + (end - start) * fraction).round() as u8
+        });
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+/// Mass spectrum settings
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct Spectrum {
+    /// Number of the labeled peaks
+    pub(crate) labels: usize,
+    /// Background subtraction
+    pub(crate) subtraction: bool,
+    /// Width of each flanking background region, minutes
+    pub(crate) flank: f64,
+}
+
+impl Spectrum {
+    fn new() -> Self {
+        Self {
+            labels: 10,
+            subtraction: false,
+            flank: 0.1,
+        }
+    }
+}
+
+impl Default for Spectrum {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash for Spectrum {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.labels.hash(state);
+        self.subtraction.hash(state);
+        self.flank.ord().hash(state);
+    }
+}
+
+/// Comparison settings
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct Comparison {
+    pub(crate) trace: Trace,
+    pub(crate) mass_to_charge: f64,
+    pub(crate) tolerance: f64,
+    pub(crate) normalization: Normalization,
+    /// Fraction of the highest trace
+    pub(crate) offset: f64,
+}
+
+impl Comparison {
+    fn new() -> Self {
+        Self {
+            trace: Trace::TotalIonChromatogram,
+            mass_to_charge: 74.0,
+            tolerance: 0.5,
+            normalization: Normalization::None,
+            offset: 0.0,
+        }
+    }
+}
+
+impl Default for Comparison {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash for Comparison {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trace.hash(state);
+        self.mass_to_charge.ord().hash(state);
+        self.tolerance.ord().hash(state);
+        self.normalization.hash(state);
+        self.offset.ord().hash(state);
+    }
+}
+
+/// Compared trace
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum Trace {
+    #[default]
+    TotalIonChromatogram,
+    ExtractedIonChromatogram,
+}
+
+impl Trace {
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::TotalIonChromatogram => "TIC",
+            Self::ExtractedIonChromatogram => "EIC",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Self::TotalIonChromatogram => "Total ion chromatogram",
+            Self::ExtractedIonChromatogram => "Extracted ion chromatogram",
+        }
+    }
+}
+
+/// Per run normalization
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum Normalization {
+    #[default]
+    None,
+    Maximum,
+    Area,
+}
+
+impl Normalization {
+    pub(crate) fn text(&self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Maximum => "Maximum",
+            Self::Area => "Area",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Self::None => "Raw signal",
+            Self::Maximum => "Signal divided by the maximum of the run",
+            Self::Area => "Signal divided by the area of the run",
+        }
+    }
 }
 
 /// Plot settings
@@ -477,3 +1710,20 @@ impl Default for Signal {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields() -> ron::error::SpannedResult<()> {
+        // Settings saved before a field was added
+        assert_eq!(ron::from_str::<Settings>("()")?, Settings::new());
+        let settings = ron::from_str::<Settings>("(explode: true, smoothing: (window: 5))")?;
+        assert!(settings.explode);
+        assert_eq!(settings.smoothing.window, 5);
+        assert_eq!(settings.smoothing.order, Smoothing::new().order);
+        assert_eq!(settings.peaks, Peaks::new());
+        Ok(())
+    }
+}
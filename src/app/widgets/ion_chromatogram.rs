@@ -1,5 +1,5 @@
 use super::signal::SignalWidget;
-use crate::app::states::settings::Settings;
+use crate::app::{notifications, states::settings::Settings};
 use egui::{Direction, Layout, Response, RichText, Ui, Widget};
 use egui_extras::{Column, TableBuilder};
 use egui_phosphor::regular::LIST;
@@ -19,8 +19,17 @@ impl IonChromatogram<'_> {
     fn show(&self, ui: &mut Ui) -> PolarsResult<Response> {
         let height = ui.spacing().interact_size.y;
         let width = ui.spacing().interact_size.x;
-        let ion_chromatogram = self.data_frame["ExtractedIonChromatogram"].list().unwrap();
-        let ion_chromatogram_series = ion_chromatogram.get_as_series(self.row_index).unwrap();
+        let ion_chromatogram = self.data_frame["ExtractedIonChromatogram"].list()?;
+        let ion_chromatogram_series = ion_chromatogram
+            .get_as_series(self.row_index)
+            .ok_or_else(|| polars_err!(OutOfBounds: "row index {}", self.row_index))?;
+        let retention_time_signal = ion_chromatogram_series.struct_()?;
+        let retention_time_series = retention_time_signal.field_by_name("RetentionTime")?;
+        let retention_time = retention_time_series.i32()?;
+        let signal_series = retention_time_signal
+            .field_by_name("Signal")?
+            .cast(&DataType::Float64)?;
+        let signal = signal_series.f64()?;
         let t = ion_chromatogram_series.iter();
         let response = ui
             .horizontal(|ui| {
@@ -61,11 +70,6 @@ impl IonChromatogram<'_> {
                 ui.visuals_mut().button_frame = false;
                 ui.menu_button(RichText::new(LIST), |ui| {
                     let total_rows = ion_chromatogram_series.len();
-                    let retention_time_signal = ion_chromatogram_series.struct_().unwrap();
-                    let retention_time_series = retention_time_signal
-                        .field_by_name("RetentionTime")
-                        .unwrap();
-                    let signal_series = retention_time_signal.field_by_name("Signal").unwrap();
                     TableBuilder::new(ui)
                         .cell_layout(Layout::centered_and_justified(Direction::LeftToRight))
                         .column(Column::auto_with_initial_suggestion(width))
@@ -92,26 +96,17 @@ impl IonChromatogram<'_> {
                                 });
                                 // Retention time
                                 row.col(|ui| {
-                                    let retention_time = retention_time_series.i32().unwrap();
-                                    let value = retention_time.get(row_index).unwrap();
-                                    let formated = self.settings.retention_time.format(value);
-                                    ui.label(formated).on_hover_text(formated.precision(None));
+                                    if let Some(value) = retention_time.get(row_index) {
+                                        let formated = self.settings.retention_time.format(value);
+                                        ui.label(formated).on_hover_text(formated.precision(None));
+                                    }
                                 });
                                 // Signal
                                 row.col(|ui| {
-                                    if self.settings.signal.normalize {
-                                        let signal = signal_series.f64().unwrap();
-                                        ui.add(
-                                            SignalWidget::new(signal.get(row_index))
-                                                .precision(Some(self.settings.signal.precision)),
-                                        );
-                                    } else {
-                                        let signal = signal_series.u16().unwrap();
-                                        ui.add(
-                                            SignalWidget::new(signal.get(row_index))
-                                                .precision(Some(self.settings.signal.precision)),
-                                        );
-                                    };
+                                    ui.add(
+                                        SignalWidget::new(signal.get(row_index))
+                                            .precision(Some(self.settings.signal.precision)),
+                                    );
                                 });
                             });
                         });
@@ -124,6 +119,10 @@ impl IonChromatogram<'_> {
 
 impl Widget for IonChromatogram<'_> {
     fn ui(self, ui: &mut Ui) -> Response {
-        self.show(ui).expect("show ion chromatogram widget")
+        let row_index = self.row_index;
+        self.show(ui).unwrap_or_else(|error| {
+            notifications::error(format!("Ion chromatogram {row_index}"), error);
+            ui.label("")
+        })
     }
 }
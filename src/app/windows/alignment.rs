@@ -0,0 +1,315 @@
+use crate::{
+    app::computers::alignment::{
+        anchors, apply, chromatogram, correlation_optimized_warping, warp,
+    },
+    utils::hash::{HashedDataFrame, HashedMetaDataFrame},
+};
+use anyhow::Result;
+use egui::{Button, ComboBox, Context, DragValue, Grid, Id, Ui, Window};
+use egui_ext::color;
+use egui_phosphor::regular::ALIGN_CENTER_HORIZONTAL;
+use egui_plot::{Legend, Line, Plot, Points};
+use metadata::{NAME, polars::MetaDataFrame};
+
+/// Provenance keys of the aligned frames
+const ALIGNMENT: &str = "Alignment";
+const REFERENCE: &str = "Reference";
+/// Points of the warp function plot
+const POINTS: usize = 200;
+
+/// Retention time alignment window
+#[derive(Debug)]
+pub(crate) struct AlignmentWindow {
+    pub(crate) open: bool,
+    frames: Vec<HashedMetaDataFrame>,
+    reference: usize,
+    /// Aligned runs
+    selected: Vec<bool>,
+    method: Method,
+    /// Anchor peaks: minimal height, fraction of the maximum
+    min_height: f64,
+    /// Anchor peaks: maximal shift, minutes
+    max_shift: f64,
+    /// Correlation optimized warping: number of segments
+    segments: usize,
+    /// Correlation optimized warping: boundary slack, points
+    slack: usize,
+    /// Anchors of the selected runs, by frame index
+    warps: Vec<(usize, Vec<(f64, f64)>)>,
+    error: Option<String>,
+}
+
+impl AlignmentWindow {
+    pub(crate) fn new(frames: Vec<HashedMetaDataFrame>) -> Self {
+        Self {
+            open: true,
+            reference: 0,
+            selected: (0..frames.len()).map(|index| index != 0).collect(),
+            frames,
+            method: Method::Anchors,
+            min_height: 0.05,
+            max_shift: 0.5,
+            segments: 20,
+            slack: 10,
+            warps: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Shows the window, returns the aligned frames once applied
+    pub(crate) fn show(&mut self, ctx: &Context) -> Vec<HashedMetaDataFrame> {
+        let mut open = self.open;
+        let mut frames = Vec::new();
+        Window::new(format!(
+            "{ALIGN_CENTER_HORIZONTAL} Retention time alignment"
+        ))
+        .id(Id::new("Alignment"))
+        .open(&mut open)
+        .show(ctx, |ui| frames = self.ui(ui));
+        self.open &= open;
+        frames
+    }
+
+    fn ui(&mut self, ui: &mut Ui) -> Vec<HashedMetaDataFrame> {
+        if self.frames.len() < 2 {
+            ui.label("Load at least two runs to align");
+            return Vec::new();
+        }
+        let previous = (
+            self.reference,
+            self.selected.clone(),
+            self.method,
+            [self.min_height, self.max_shift],
+            [self.segments, self.slack],
+        );
+        self.parameters(ui);
+        if previous
+            != (
+                self.reference,
+                self.selected.clone(),
+                self.method,
+                [self.min_height, self.max_shift],
+                [self.segments, self.slack],
+            )
+        {
+            self.warps.clear();
+        }
+        ui.separator();
+        if !self.warps.is_empty() {
+            self.plot(ui);
+            ui.separator();
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        let mut frames = Vec::new();
+        ui.horizontal(|ui| {
+            if ui.button("Compute").clicked() {
+                match self.compute() {
+                    Ok(warps) => {
+                        self.warps = warps;
+                        self.error = None;
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            if ui
+                .add_enabled(!self.warps.is_empty(), Button::new("Apply"))
+                .on_hover_text("Open the aligned runs as new panes")
+                .clicked()
+            {
+                match self.apply() {
+                    Ok(aligned) => {
+                        frames = aligned;
+                        self.open = false;
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            if ui.button("Cancel").clicked() {
+                self.open = false;
+            }
+        });
+        frames
+    }
+
+    fn parameters(&mut self, ui: &mut Ui) {
+        Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            ui.label("Reference");
+            ComboBox::from_id_salt("AlignmentReference")
+                .selected_text(name(&self.frames[self.reference]))
+                .show_ui(ui, |ui| {
+                    for (index, frame) in self.frames.iter().enumerate() {
+                        ui.selectable_value(&mut self.reference, index, name(frame));
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Runs");
+            ui.vertical(|ui| {
+                for (index, frame) in self.frames.iter().enumerate() {
+                    if index != self.reference {
+                        ui.checkbox(&mut self.selected[index], name(frame));
+                    }
+                }
+            });
+            ui.end_row();
+
+            ui.label("Method");
+            ComboBox::from_id_salt("AlignmentMethod")
+                .selected_text(self.method.text())
+                .show_ui(ui, |ui| {
+                    for method in [Method::Anchors, Method::CorrelationOptimizedWarping] {
+                        ui.selectable_value(&mut self.method, method, method.text())
+                            .on_hover_text(method.description());
+                    }
+                })
+                .response
+                .on_hover_text(self.method.description());
+            ui.end_row();
+
+            match self.method {
+                Method::Anchors => {
+                    ui.label("Minimal height");
+                    ui.add(
+                        DragValue::new(&mut self.min_height)
+                            .range(0.0..=1.0)
+                            .speed(0.01),
+                    )
+                    .on_hover_text("Minimal anchor peak height, fraction of the maximum");
+                    ui.end_row();
+
+                    ui.label("Maximal shift");
+                    ui.add(
+                        DragValue::new(&mut self.max_shift)
+                            .range(0.0..=f64::MAX)
+                            .speed(0.01),
+                    )
+                    .on_hover_text("Maximal anchor peak shift, minutes");
+                    ui.end_row();
+                }
+                Method::CorrelationOptimizedWarping => {
+                    ui.label("Segments");
+                    ui.add(DragValue::new(&mut self.segments).range(1..=usize::MAX))
+                        .on_hover_text("Number of warped segments");
+                    ui.end_row();
+
+                    ui.label("Slack");
+                    ui.add(DragValue::new(&mut self.slack))
+                        .on_hover_text("Maximal movement of the segment boundaries, points");
+                    ui.end_row();
+                }
+            }
+        });
+    }
+
+    /// Warp function plot, shift to the reference against the run retention
+    /// time
+    fn plot(&self, ui: &mut Ui) {
+        Plot::new("Alignment")
+            .height(ui.available_height().min(200.0).max(100.0))
+            .legend(Legend::default())
+            .x_axis_label("Retention time")
+            .y_axis_label("Shift")
+            .show(ui, |ui| {
+                for (index, anchors) in &self.warps {
+                    let name = name(&self.frames[*index]);
+                    let color = color(*index);
+                    if let (Some(first), Some(last)) = (anchors.first(), anchors.last()) {
+                        let step = (last.0 - first.0) / (POINTS - 1) as f64;
+                        let points = (0..POINTS)
+                            .map(|point| {
+                                let retention_time = first.0 + point as f64 * step;
+                                [
+                                    retention_time,
+                                    warp(anchors, retention_time) - retention_time,
+                                ]
+                            })
+                            .collect::<Vec<_>>();
+                        ui.line(Line::new(&name, points).color(color));
+                    }
+                    let points = anchors
+                        .iter()
+                        .map(|&(run, reference)| [run, reference - run])
+                        .collect::<Vec<_>>();
+                    ui.points(Points::new(&name, points).color(color).radius(3.0));
+                }
+            });
+    }
+
+    /// Anchors of the selected runs against the reference
+    fn compute(&self) -> Result<Vec<(usize, Vec<(f64, f64)>)>> {
+        let (reference_time, reference) = chromatogram(&self.frames[self.reference].data)?;
+        let mut warps = Vec::new();
+        for (index, frame) in self.frames.iter().enumerate() {
+            if index == self.reference || !self.selected[index] {
+                continue;
+            }
+            let (run_time, run) = chromatogram(&frame.data)?;
+            let anchors = match self.method {
+                Method::Anchors => anchors(
+                    (&reference_time, &reference),
+                    (&run_time, &run),
+                    self.min_height,
+                    self.max_shift,
+                ),
+                Method::CorrelationOptimizedWarping => correlation_optimized_warping(
+                    (&reference_time, &reference),
+                    (&run_time, &run),
+                    self.segments,
+                    self.slack,
+                ),
+            };
+            warps.push((index, anchors));
+        }
+        Ok(warps)
+    }
+
+    /// Aligned frames with the provenance in the metadata
+    fn apply(&self) -> Result<Vec<HashedMetaDataFrame>> {
+        let reference = name(&self.frames[self.reference]);
+        let mut frames = Vec::with_capacity(self.warps.len());
+        for (index, anchors) in &self.warps {
+            let frame = &self.frames[*index];
+            let data_frame = apply(&frame.data, anchors)?;
+            let mut meta = frame.meta.clone();
+            meta.insert(NAME.to_owned(), format!("{} (aligned)", name(frame)));
+            meta.insert(REFERENCE.to_owned(), reference.clone());
+            meta.insert(
+                ALIGNMENT.to_owned(),
+                format!("{}, {} anchors", self.method.text(), anchors.len()),
+            );
+            frames.push(MetaDataFrame::new(meta, HashedDataFrame::new(data_frame)?));
+        }
+        Ok(frames)
+    }
+}
+
+/// Alignment method
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Method {
+    Anchors,
+    CorrelationOptimizedWarping,
+}
+
+impl Method {
+    fn text(&self) -> &'static str {
+        match self {
+            Self::Anchors => "Anchor peaks",
+            Self::CorrelationOptimizedWarping => "COW",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::Anchors => "Piecewise linear warping through the matched peak apexes",
+            Self::CorrelationOptimizedWarping => {
+                "Correlation optimized warping of the total ion chromatograms"
+            }
+        }
+    }
+}
+
+fn name(frame: &HashedMetaDataFrame) -> String {
+    frame.meta.get(NAME).cloned().unwrap_or_default()
+}
@@ -0,0 +1,205 @@
+use crate::{
+    app::{
+        readers::{
+            delimited::{Mapping, Options},
+            stem,
+        },
+        states::settings::TimeUnits,
+    },
+    utils::hash::HashedMetaDataFrame,
+};
+use egui::{ComboBox, Context, Direction, DragValue, Grid, Id, Layout, ScrollArea, Ui, Window};
+use egui_extras::{Column, TableBuilder};
+use egui_phosphor::regular::FILE_CSV;
+use metadata::{Metadata, NAME};
+use polars::prelude::*;
+
+/// Number of preview rows
+const PREVIEW: usize = 10;
+
+/// Delimited text import window
+#[derive(Debug)]
+pub(crate) struct ImportWindow {
+    pub(crate) open: bool,
+    name: String,
+    bytes: Vec<u8>,
+    options: Options,
+    mapping: Mapping,
+    data_frame: PolarsResult<DataFrame>,
+    error: Option<String>,
+}
+
+impl ImportWindow {
+    pub(crate) fn new(name: String, bytes: Vec<u8>) -> Self {
+        let options = Options::guess(&name, &bytes);
+        let data_frame = options.parse(&bytes);
+        let mapping = match &data_frame {
+            Ok(data_frame) => Mapping::guess(data_frame),
+            Err(_) => Mapping::default(),
+        };
+        Self {
+            open: true,
+            name,
+            bytes,
+            options,
+            mapping,
+            data_frame,
+            error: None,
+        }
+    }
+
+    /// Shows the window, returns the converted frame once imported
+    pub(crate) fn show(&mut self, ctx: &Context) -> Option<HashedMetaDataFrame> {
+        let mut open = self.open;
+        let mut frame = None;
+        Window::new(format!("{FILE_CSV} Import {}", self.name))
+            .id(Id::new("Import").with(&self.name))
+            .open(&mut open)
+            .show(ctx, |ui| frame = self.ui(ui));
+        self.open &= open;
+        frame
+    }
+
+    fn ui(&mut self, ui: &mut Ui) -> Option<HashedMetaDataFrame> {
+        self.options(ui);
+        ui.separator();
+        let data_frame = match &self.data_frame {
+            Ok(data_frame) => data_frame,
+            Err(error) => {
+                ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+                return None;
+            }
+        };
+        let names = data_frame.get_column_names_owned();
+        Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            column(
+                ui,
+                "Retention time",
+                &mut self.mapping.retention_time,
+                &names,
+            );
+            ComboBox::from_id_salt("ImportRetentionTimeUnits")
+                .selected_text(self.mapping.units.singular())
+                .show_ui(ui, |ui| {
+                    for units in [TimeUnits::Millisecond, TimeUnits::Second, TimeUnits::Minute] {
+                        ui.selectable_value(&mut self.mapping.units, units, units.singular())
+                            .on_hover_text(units.abbreviation());
+                    }
+                })
+                .response
+                .on_hover_text("Retention time units");
+            ui.end_row();
+            column(
+                ui,
+                "Mass to charge",
+                &mut self.mapping.mass_to_charge,
+                &names,
+            );
+            ui.end_row();
+            column(ui, "Signal", &mut self.mapping.signal, &names);
+            ui.end_row();
+        });
+        ui.separator();
+        ScrollArea::horizontal().show(ui, |ui| preview(ui, data_frame));
+        ui.separator();
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        let mut frame = None;
+        ui.horizontal(|ui| {
+            if ui.button("Import").clicked() {
+                let mut meta = Metadata::default();
+                meta.insert(NAME.to_owned(), stem(&self.name));
+                match self.mapping.convert(data_frame, meta) {
+                    Ok(converted) => {
+                        frame = Some(converted);
+                        self.open = false;
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            if ui.button("Cancel").clicked() {
+                self.open = false;
+            }
+        });
+        frame
+    }
+
+    fn options(&mut self, ui: &mut Ui) {
+        let options = self.options;
+        Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            ui.label("Separator");
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.options.separator, b',', "Comma");
+                ui.selectable_value(&mut self.options.separator, b'\t', "Tab");
+                ui.selectable_value(&mut self.options.separator, b';', "Semicolon");
+                ui.selectable_value(&mut self.options.separator, b' ', "Space");
+            });
+            ui.end_row();
+
+            ui.label("Header");
+            ui.checkbox(&mut self.options.has_header, "")
+                .on_hover_text("First row contains column names");
+            ui.end_row();
+
+            ui.label("Skip rows");
+            ui.add(DragValue::new(&mut self.options.skip_rows))
+                .on_hover_text("Rows to skip before the header");
+            ui.end_row();
+
+            ui.label("Decimal comma");
+            ui.checkbox(&mut self.options.decimal_comma, "");
+            ui.end_row();
+        });
+        if self.options != options {
+            self.data_frame = self.options.parse(&self.bytes);
+            if let Ok(data_frame) = &self.data_frame {
+                self.mapping = Mapping {
+                    units: self.mapping.units,
+                    ..Mapping::guess(data_frame)
+                };
+            }
+            self.error = None;
+        }
+    }
+}
+
+fn column(ui: &mut Ui, text: &str, selected: &mut Option<PlSmallStr>, names: &[PlSmallStr]) {
+    ui.label(text);
+    ComboBox::from_id_salt(text)
+        .selected_text(selected.as_deref().unwrap_or_default())
+        .show_ui(ui, |ui| {
+            for name in names {
+                ui.selectable_value(selected, Some(name.clone()), name.as_str());
+            }
+        });
+}
+
+fn preview(ui: &mut Ui, data_frame: &DataFrame) {
+    let height = ui.spacing().interact_size.y;
+    let total_rows = data_frame.height().min(PREVIEW);
+    TableBuilder::new(ui)
+        .cell_layout(Layout::centered_and_justified(Direction::LeftToRight))
+        .columns(Column::auto(), data_frame.width())
+        .auto_shrink([true, true])
+        .striped(true)
+        .header(height, |mut row| {
+            for name in data_frame.get_column_names() {
+                row.col(|ui| {
+                    ui.heading(name.as_str());
+                });
+            }
+        })
+        .body(|body| {
+            body.rows(height, total_rows, |mut row| {
+                let row_index = row.index();
+                for column in data_frame.get_columns() {
+                    row.col(|ui| {
+                        if let Ok(value) = column.get(row_index) {
+                            ui.label(value.to_string());
+                        }
+                    });
+                }
+            });
+        });
+}
@@ -0,0 +1,4 @@
+pub(crate) mod alignment;
+pub(crate) mod import;
+pub(crate) mod save;
+pub(crate) mod subtraction;
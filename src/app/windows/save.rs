@@ -0,0 +1,167 @@
+use crate::{
+    app::{
+        computers::table::{Computer as TableComputer, Key as TableKey},
+        data::{Data, Format},
+        file_dialog, notifications,
+        readers::stem,
+        states::settings::Settings,
+    },
+    utils::hash::HashedMetaDataFrame,
+};
+use anyhow::Result;
+use egui::{Button, ComboBox, Context, Grid, Id, Ui, Window};
+use egui_phosphor::regular::FLOPPY_DISK;
+use metadata::{NAME, polars::MetaDataFrame};
+use std::collections::HashSet;
+
+/// Save window
+///
+/// Saves the frames of the chosen panes, a single frame to a chosen path, a
+/// few to a chosen folder. The web build downloads the file instead, a few
+/// bundled into a zip archive.
+#[derive(Debug)]
+pub(crate) struct SaveWindow {
+    pub(crate) open: bool,
+    /// Frame, settings and whether to save each active pane
+    panes: Vec<(HashedMetaDataFrame, Settings, bool)>,
+    format: Format,
+    scope: Scope,
+    error: Option<String>,
+}
+
+impl SaveWindow {
+    pub(crate) fn new(panes: Vec<(HashedMetaDataFrame, Settings)>) -> Self {
+        Self {
+            open: true,
+            panes: panes
+                .into_iter()
+                .map(|(frame, settings)| (frame, settings, true))
+                .collect(),
+            format: Format::MetaRon,
+            scope: Scope::Raw,
+            error: None,
+        }
+    }
+
+    pub(crate) fn show(&mut self, ctx: &Context) {
+        let mut open = self.open;
+        Window::new(format!("{FLOPPY_DISK} Save"))
+            .id(Id::new("Save"))
+            .open(&mut open)
+            .show(ctx, |ui| self.ui(ui));
+        self.open &= open;
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        if self.panes.is_empty() {
+            ui.label("Load a run to save");
+            return;
+        }
+        for (index, (frame, _, selected)) in self.panes.iter_mut().enumerate() {
+            ui.checkbox(selected, format!("{index}: {}", name(frame)))
+                .on_hover_text(format!("{} points", frame.data.height()));
+        }
+        ui.separator();
+        Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            ui.label("Format");
+            ComboBox::from_id_salt("Format")
+                .selected_text(self.format.text())
+                .show_ui(ui, |ui| {
+                    for format in Format::ALL {
+                        ui.selectable_value(&mut self.format, format, format.text());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Scope");
+            ComboBox::from_id_salt("Scope")
+                .selected_text(self.scope.text())
+                .show_ui(ui, |ui| {
+                    for scope in [Scope::Raw, Scope::Computed] {
+                        ui.selectable_value(&mut self.scope, scope, scope.text())
+                            .on_hover_text(scope.description());
+                    }
+                });
+            ui.end_row();
+        });
+        ui.separator();
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        ui.horizontal(|ui| {
+            let selected = self.panes.iter().any(|(.., selected)| *selected);
+            if ui.add_enabled(selected, Button::new("Save")).clicked() {
+                match self.save() {
+                    Ok(()) => self.open = false,
+                    Err(error) => {
+                        self.error = Some(format!("{error:#}"));
+                        notifications::error("Save", error);
+                    }
+                }
+            }
+            if ui.button("Cancel").clicked() {
+                self.open = false;
+            }
+        });
+    }
+
+    fn save(&self) -> Result<()> {
+        let mut files = Vec::new();
+        let mut names = HashSet::new();
+        for (frame, settings, selected) in &self.panes {
+            if !selected {
+                continue;
+            }
+            let frame = match self.scope {
+                Scope::Raw => frame.clone(),
+                Scope::Computed => MetaDataFrame::new(
+                    frame.meta.clone(),
+                    TableComputer.try_compute(TableKey::new(&frame.data, settings))?,
+                ),
+            };
+            // Panes of the same run (or unnamed runs) would overwrite each other
+            let stem = stem(&name(&frame));
+            let extension = self.format.extension();
+            let mut name = format!("{stem}.{extension}");
+            let mut index = 1;
+            while !names.insert(name.clone()) {
+                name = format!("{stem}.{index}.{extension}");
+                index += 1;
+            }
+            files.push((name, Data { frame }.bytes(self.format)?));
+        }
+        file_dialog::save(files)
+    }
+}
+
+/// Save scope
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Scope {
+    Raw,
+    Computed,
+}
+
+impl Scope {
+    fn text(&self) -> &'static str {
+        match self {
+            Self::Raw => "Raw",
+            Self::Computed => "Computed",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::Raw => "Loaded points (retention time, mass to charge, signal)",
+            Self::Computed => "Frame shown by the table pane with the pane settings",
+        }
+    }
+}
+
+fn name(frame: &HashedMetaDataFrame) -> String {
+    frame
+        .meta
+        .get(NAME)
+        .filter(|name| !name.is_empty())
+        .cloned()
+        .unwrap_or_else(|| "df".to_owned())
+}
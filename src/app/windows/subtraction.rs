@@ -0,0 +1,235 @@
+use crate::{app::readers::Builder, r#const::*, utils::hash::HashedMetaDataFrame};
+use anyhow::Result;
+use egui::{Button, ComboBox, Context, DragValue, Grid, Id, Ui, Window};
+use egui_phosphor::regular::MINUS_SQUARE;
+use itertools::{Itertools, izip};
+use metadata::NAME;
+use polars::prelude::*;
+
+/// Provenance keys of the subtracted frame
+const SAMPLE: &str = "Sample";
+const BLANK: &str = "Blank";
+const SUBTRACTION: &str = "Subtraction";
+
+/// Blank subtraction window
+#[derive(Debug)]
+pub(crate) struct SubtractionWindow {
+    pub(crate) open: bool,
+    frames: Vec<HashedMetaDataFrame>,
+    sample: usize,
+    blank: usize,
+    /// Mass to charge tolerance, Da
+    tolerance: f32,
+    /// Blank scale factor
+    scale: f64,
+    error: Option<String>,
+}
+
+impl SubtractionWindow {
+    pub(crate) fn new(frames: Vec<HashedMetaDataFrame>) -> Self {
+        Self {
+            open: true,
+            blank: if frames.len() > 1 { 1 } else { 0 },
+            frames,
+            sample: 0,
+            tolerance: 0.5,
+            scale: 1.0,
+            error: None,
+        }
+    }
+
+    /// Shows the window, returns the subtracted frame once computed
+    pub(crate) fn show(&mut self, ctx: &Context) -> Option<HashedMetaDataFrame> {
+        let mut open = self.open;
+        let mut frame = None;
+        Window::new(format!("{MINUS_SQUARE} Blank subtraction"))
+            .id(Id::new("Subtraction"))
+            .open(&mut open)
+            .show(ctx, |ui| frame = self.ui(ui));
+        self.open &= open;
+        frame
+    }
+
+    fn ui(&mut self, ui: &mut Ui) -> Option<HashedMetaDataFrame> {
+        if self.frames.len() < 2 {
+            ui.label("Load a sample and a blank run to subtract");
+            return None;
+        }
+        Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            frame(ui, "Sample", &mut self.sample, &self.frames);
+            ui.end_row();
+            frame(ui, "Blank", &mut self.blank, &self.frames);
+            ui.end_row();
+
+            ui.label("Mass to charge tolerance");
+            ui.add(
+                DragValue::new(&mut self.tolerance)
+                    .range(0.0..=f32::MAX)
+                    .speed(0.01),
+            )
+            .on_hover_text("Blank ions within this distance (Da) are subtracted");
+            ui.end_row();
+
+            ui.label("Scale");
+            ui.add(
+                DragValue::new(&mut self.scale)
+                    .range(0.0..=f64::MAX)
+                    .speed(0.01),
+            )
+            .on_hover_text("Blank signal multiplier");
+            ui.end_row();
+        });
+        ui.separator();
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        let mut frame = None;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.sample != self.blank, Button::new("Subtract"))
+                .clicked()
+            {
+                match subtract(
+                    &self.frames[self.sample],
+                    &self.frames[self.blank],
+                    self.tolerance,
+                    self.scale,
+                ) {
+                    Ok(subtracted) => {
+                        frame = Some(subtracted);
+                        self.open = false;
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            if ui.button("Cancel").clicked() {
+                self.open = false;
+            }
+        });
+        frame
+    }
+}
+
+fn frame(ui: &mut Ui, text: &str, selected: &mut usize, frames: &[HashedMetaDataFrame]) {
+    ui.label(text);
+    ComboBox::from_id_salt(text)
+        .selected_text(name(&frames[*selected]))
+        .show_ui(ui, |ui| {
+            for (index, frame) in frames.iter().enumerate() {
+                ui.selectable_value(selected, index, name(frame));
+            }
+        });
+}
+
+fn name(frame: &HashedMetaDataFrame) -> String {
+    frame.meta.get(NAME).cloned().unwrap_or_default()
+}
+
+/// Scan: retention time (ms) and mass spectrum sorted by mass to charge
+type Scan = (i32, Vec<(f32, f64)>);
+
+/// Subtracts the blank from the sample
+///
+/// The blank is linearly interpolated between the scans bracketing each
+/// sample scan, the nearest blank ion within the tolerance is subtracted and
+/// non positive signals are dropped.
+fn subtract(
+    sample: &HashedMetaDataFrame,
+    blank: &HashedMetaDataFrame,
+    tolerance: f32,
+    scale: f64,
+) -> Result<HashedMetaDataFrame> {
+    let blank_scans = scans(&blank.data)?;
+    let mut builder = Builder::default();
+    for (retention_time, mass_spectrum) in scans(&sample.data)? {
+        // Bracketing blank scans and the weight of the later one
+        let index = blank_scans.partition_point(|(blank, _)| *blank <= retention_time);
+        let bracket = match (index.checked_sub(1), blank_scans.get(index)) {
+            (Some(previous), Some(next)) => {
+                let (t0, previous) = &blank_scans[previous];
+                let (t1, next) = next;
+                let weight = (retention_time - t0) as f64 / (t1 - t0) as f64;
+                Some([(previous, 1.0 - weight), (next, weight)])
+            }
+            (Some(previous), None) => Some([
+                (&blank_scans[previous].1, 1.0),
+                (&blank_scans[previous].1, 0.0),
+            ]),
+            (None, Some((_, next))) => Some([(next, 1.0), (next, 0.0)]),
+            (None, None) => None,
+        };
+        for (mass_to_charge, signal) in mass_spectrum {
+            let background = bracket.map_or(0.0, |bracket| {
+                bracket
+                    .iter()
+                    .map(|(mass_spectrum, weight)| {
+                        weight * nearest(mass_spectrum, mass_to_charge, tolerance)
+                    })
+                    .sum()
+            });
+            let signal = signal - scale * background;
+            if signal > 0.0 {
+                builder.push(retention_time, mass_to_charge, signal);
+            }
+        }
+    }
+    // Provenance
+    let mut meta = sample.meta.clone();
+    let (sample, blank) = (name(sample), name(blank));
+    meta.insert(NAME.to_owned(), format!("{sample} - {blank}"));
+    meta.insert(SAMPLE.to_owned(), sample);
+    meta.insert(BLANK.to_owned(), blank);
+    meta.insert(
+        SUBTRACTION.to_owned(),
+        format!("m/z tolerance {tolerance} Da, scale {scale}, retention time interpolated"),
+    );
+    builder.build(meta)
+}
+
+/// Signal of the nearest ion within the tolerance
+fn nearest(mass_spectrum: &[(f32, f64)], mass_to_charge: f32, tolerance: f32) -> f64 {
+    let index = mass_spectrum.partition_point(|(other, _)| *other < mass_to_charge);
+    [index.checked_sub(1), Some(index)]
+        .into_iter()
+        .flatten()
+        .filter_map(|index| mass_spectrum.get(index))
+        .filter(|(other, _)| (other - mass_to_charge).abs() <= tolerance)
+        .min_by(|left, right| {
+            (left.0 - mass_to_charge)
+                .abs()
+                .total_cmp(&(right.0 - mass_to_charge).abs())
+        })
+        .map_or(0.0, |(_, signal)| *signal)
+}
+
+/// Scans of a long frame sorted by retention time
+fn scans(data_frame: &DataFrame) -> PolarsResult<Vec<Scan>> {
+    let data_frame = data_frame
+        .clone()
+        .lazy()
+        .select([
+            col(RETENTION_TIME).cast(DataType::Int32),
+            col(MASS_TO_CHARGE).cast(DataType::Float32),
+            col(SIGNAL).cast(DataType::Float64),
+        ])
+        .sort([RETENTION_TIME, MASS_TO_CHARGE], Default::default())
+        .collect()?;
+    let points = izip!(
+        data_frame[RETENTION_TIME].i32()?,
+        data_frame[MASS_TO_CHARGE].f32()?,
+        data_frame[SIGNAL].f64()?,
+    )
+    .filter_map(|(retention_time, mass_to_charge, signal)| {
+        Some((retention_time?, mass_to_charge?, signal?))
+    });
+    Ok(points
+        .chunk_by(|(retention_time, ..)| *retention_time)
+        .into_iter()
+        .map(|(retention_time, chunk)| {
+            let mass_spectrum = chunk
+                .map(|(_, mass_to_charge, signal)| (mass_to_charge, signal))
+                .collect();
+            (retention_time, mass_spectrum)
+        })
+        .collect())
+}
@@ -1,3 +1,4 @@
+pub(crate) const BASELINE: &str = "Baseline";
 pub(crate) const COUNT: &str = "Count";
 pub(crate) const MASS_SPECTRUM: &str = "MassSpectrum";
 pub(crate) const MASS_TO_CHARGE: &str = "MassToCharge";
@@ -2,6 +2,8 @@
 #![feature(try_trait_v2)]
 
 pub use app::App;
+#[cfg(not(target_arch = "wasm32"))]
+pub use app::cli;
 
 mod app;
 mod r#const;
@@ -1,6 +1,8 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
 use ms_viewer::App;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
 
 // Native
 #[cfg(not(target_arch = "wasm32"))]
@@ -11,11 +13,35 @@ fn main() -> eframe::Result<()> {
     // Log to stdout (if you run with `RUST_LOG=debug`).
     tracing_subscriber::fmt::init();
 
+    // Headless commands
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if ms_viewer::cli::is_command(&args) {
+        // The release build has no console of its own, write to the console
+        // of the shell
+        #[cfg(all(windows, not(debug_assertions)))]
+        unsafe {
+            use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+            AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+        if let Err(error) = ms_viewer::cli::run(&args) {
+            eprintln!("{error:#}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    // Files to open, `--plot` inserts them as plot panes instead of tables
+    let plot = args.iter().any(|arg| arg == "--plot");
+    let paths = args
+        .iter()
+        .filter(|arg| !matches!(arg.as_str(), "--plot" | "--table"))
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+
     let native_options = Default::default();
     eframe::run_native(
         "MS viewer",
         native_options,
-        Box::new(|cc| Ok(Box::new(App::new(cc)))),
+        Box::new(move |cc| Ok(Box::new(App::new(cc).open(&cc.egui_ctx, &paths, plot)))),
     )
 }
 